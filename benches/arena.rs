@@ -0,0 +1,76 @@
+// Compares the Rc trees with the arena on an expression of a million terms, (+ 0 1 2 ... 999999)
+//   cargo bench --bench arena
+// The Rc tree is parsed, evaluated and dropped on a thread with a big stack since each of those recurses once per
+// operator
+
+use project::arena;
+use project::parser;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+// Counts every allocation, so each side can report how many it made
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const TERMS: usize = 1_000_000;
+
+// Runs f, printing how long it took and how many allocations it made
+fn measure<T>(name: &str, f: impl FnOnce() -> T) -> T {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    println!(
+        "{:<12} {:>10.2?} {:>10} allocations",
+        name, elapsed, allocations
+    );
+    result
+}
+
+fn main() {
+    let mut input = String::from("(+");
+    for i in 0..TERMS {
+        input.push_str(&format!(" {}", i));
+    }
+    input.push(')');
+    let tokens = measure("lex", || parser::lex(&input));
+
+    let ast = measure("arena parse", || arena::try_parse(tokens.clone()).unwrap());
+    let value = measure("arena eval", || ast.eval_value().unwrap());
+    println!("{} nodes, {}", ast.len(), value);
+
+    std::thread::scope(|scope| {
+        std::thread::Builder::new()
+            .stack_size(1 << 30)
+            .spawn_scoped(scope, move || {
+                let exp = measure("rc parse", || parser::try_parse(tokens).unwrap());
+                let value = measure("rc eval", || exp.eval_value().unwrap());
+                println!("{}", value);
+                measure("rc drop", || drop(exp));
+                measure("arena drop", || drop(ast));
+            })
+            .unwrap();
+    });
+}
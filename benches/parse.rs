@@ -0,0 +1,83 @@
+// Parses expressions from 1MB up to 10MB, all on one line, to show that lexing and parsing take time linear in
+// the length of the input
+//   cargo bench --bench parse
+// Criterion reports the throughput for each size, which stays about the same as the input grows
+// A short expression is parsed too, which shows the cost of each call that doesn't depend on the input
+// The expression is a balanced tree of + and * forms so that nesting stays shallow, with numbers, floats, names and
+// unary minus at the leaves
+// A flat (+ 1 2 3 ...) of 10MB is parsed and evaluated as well, which folds into a chain of over a million operators
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use project::parser;
+
+const MB: usize = 1 << 20;
+
+// An expression of about len bytes, len must be at least one leaf long
+fn expression(len: usize) -> String {
+    let leaf = "(* 12 x 2.5 (- y))";
+    let mut out = String::with_capacity(len + 2 * leaf.len());
+    write_tree(len / (leaf.len() + 4), leaf, &mut out);
+    out
+}
+
+// A tree with leaves copies of leaf
+fn write_tree(leaves: usize, leaf: &str, out: &mut String) {
+    if leaves <= 1 {
+        out.push_str(leaf);
+    } else {
+        out.push_str("(+ ");
+        write_tree(leaves / 2, leaf, out);
+        out.push(' ');
+        write_tree(leaves - leaves / 2, leaf, out);
+        out.push(')');
+    }
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    group.sample_size(10);
+    for mb in [1, 2, 4, 8, 10] {
+        let input = expression(mb * MB);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{}MB", mb)),
+            &input,
+            |b, input| b.iter(|| parser::try_parse(parser::lex(input)).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+// One line of a formula, where nothing is compiled or set up again for each call
+fn parse_small(c: &mut Criterion) {
+    let input = "(+ 1 (* 2 x) (- y 3.5))";
+    c.bench_function("parse/small", |b| {
+        b.iter(|| parser::try_parse(parser::lex(input)).unwrap())
+    });
+}
+
+// A single form with an operand for every few bytes, where the tree is as deep as there are operands
+fn parse_flat(c: &mut Criterion) {
+    let mut input = String::from("(+");
+    let mut i = 0;
+    while input.len() < 10 * MB {
+        input.push_str(&format!(" {}", i % 1000));
+        i += 1;
+    }
+    input.push(')');
+    let mut group = c.benchmark_group("parse");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("flat 10MB", |b| {
+        b.iter(|| {
+            parser::try_parse(parser::lex(&input))
+                .unwrap()
+                .try_eval()
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parse, parse_small, parse_flat);
+criterion_main!(benches);
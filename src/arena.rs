@@ -0,0 +1,420 @@
+// The arithmetic trees, kept in one Vec with children referred to by index instead of each node being its own Rc
+//   let ast = arena::try_parse(lex("(- 0 1 2 3)"))?;
+//   ast.eval() => -6, ast.to_string() => (- (- (- 0 1) 2) 3)
+// Parsing makes one allocation per Vec rather than one per node, and evaluating and printing walk the tree with
+// a stack of their own, so a chain a million operators deep neither overflows the stack nor takes long to drop
+// Like infix and rpn, only numbers, names and + - * / % ^ have an arena form, the forms and their k-ary and unary
+// versions parse to the same trees the default parser builds
+
+use crate::number::{self, Number};
+use crate::operators::{Assoc, OperatorTable, Unary};
+use crate::parser::{
+    self, Atom, Atoms, BinOp, EvalError, Exp, FloatExp, LitExp, Literal, ParseError, Span, Token,
+    VarExp,
+};
+use crate::{BigInt, Env, Rational, Value};
+use std::rc::Rc;
+
+// An index into the nodes of an Ast
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NodeId(u32);
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Node {
+    Int(i64),
+    BigInt(BigInt), // Only for literals that don't fit an i64, which most evaluations then reject
+    Float(f64),
+    Var(String),
+    Binary(BinOp, NodeId, NodeId),
+}
+
+// Nodes and their spans are kept apart, evaluating only looks at a span when it fails
+#[derive(Clone, Debug)]
+pub struct Ast {
+    nodes: Vec<Node>,
+    spans: Vec<Span>,
+    root: NodeId,
+}
+
+impl Ast {
+    pub fn root(&self) -> NodeId {
+        self.root
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id.0 as usize]
+    }
+
+    pub fn span(&self, id: NodeId) -> Span {
+        self.spans[id.0 as usize]
+    }
+
+    // How many nodes the tree has
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    // Always false, an Ast has at least its root
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    // Evaluates with checked i64 arithmetic, looking variables up in env
+    pub fn eval_with(&self, env: &Env) -> Result<Value, EvalError> {
+        // A name on its own can be any value, only operands have to be numbers
+        if let Node::Var(name) = self.node(self.root) {
+            return env.get(name).ok_or_else(|| EvalError::UnboundVariable {
+                name: name.to_string(),
+                span: self.span(self.root),
+            });
+        }
+        let n = self.run(|node, span| match node {
+            Node::Int(n) => Ok(*n),
+            Node::BigInt(n) => number::literal(Literal::Int(n), span),
+            Node::Float(x) => number::literal(Literal::Float(*x), span),
+            Node::Var(name) => env
+                .get(name)
+                .ok_or_else(|| EvalError::UnboundVariable {
+                    name: name.to_string(),
+                    span,
+                })?
+                .to_num(span),
+            Node::Binary(..) => unreachable!(),
+        })?;
+        Ok(Value::Num(n))
+    }
+
+    pub fn eval_value(&self) -> Result<Value, EvalError> {
+        self.eval_with(&Env::new())
+    }
+
+    pub fn try_eval(&self) -> Result<i64, EvalError> {
+        self.eval_value()?.to_num(self.span(self.root))
+    }
+
+    // Errors, including results that don't fit in an i32, evaluate to -1000000, like Exp::eval
+    pub fn eval(&self) -> i32 {
+        match self.try_eval() {
+            Ok(n) => i32::try_from(n).unwrap_or(-1000000),
+            Err(_) => -1000000,
+        }
+    }
+
+    // Evaluates as any Number, which has no variables
+    pub fn eval_as<N: Number>(&self) -> Result<N, EvalError> {
+        self.run(|node, span| match node {
+            Node::Int(n) => number::literal(Literal::Int(&BigInt::from(*n)), span),
+            Node::BigInt(n) => number::literal(Literal::Int(n), span),
+            Node::Float(x) => number::literal(Literal::Float(*x), span),
+            Node::Var(name) => Err(EvalError::UnboundVariable {
+                name: name.to_string(),
+                span,
+            }),
+            Node::Binary(..) => unreachable!(),
+        })
+    }
+
+    pub fn eval_big(&self) -> Result<BigInt, EvalError> {
+        self.eval_as()
+    }
+
+    pub fn eval_rational(&self) -> Result<Rational, EvalError> {
+        self.eval_as()
+    }
+
+    pub fn eval_f64(&self) -> Result<f64, EvalError> {
+        self.eval_as()
+    }
+
+    // Evaluates every operand before the operator it belongs to, left before right, the order Exp evaluates in,
+    // with leaf giving the value of every node that isn't Binary
+    fn run<N: Number>(
+        &self,
+        leaf: impl Fn(&Node, Span) -> Result<N, EvalError>,
+    ) -> Result<N, EvalError> {
+        let mut values: Vec<N> = vec![];
+        self.post_order(|id| {
+            let value = match *self.node(id) {
+                Node::Binary(op, _, rhs) => {
+                    let r = values.pop().unwrap();
+                    let l = values.pop().unwrap();
+                    number::apply(op, l, r, self.span(rhs), self.span(id))?
+                }
+                ref node => leaf(node, self.span(id))?,
+            };
+            values.push(value);
+            Ok(())
+        })?;
+        Ok(values.pop().unwrap())
+    }
+
+    // The same tree as Exp trait objects, spans and all
+    pub fn to_exp(&self) -> Rc<dyn Exp> {
+        let mut built: Vec<Rc<dyn Exp>> = vec![];
+        let done: Result<(), ()> = self.post_order(|id| {
+            let span = self.span(id);
+            let exp: Rc<dyn Exp> = match self.node(id) {
+                Node::Int(n) => Rc::new(LitExp {
+                    n: BigInt::from(*n),
+                    span,
+                }),
+                Node::BigInt(n) => Rc::new(LitExp { n: n.clone(), span }),
+                Node::Float(x) => Rc::new(FloatExp { x: *x, span }),
+                Node::Var(name) => Rc::new(VarExp {
+                    name: name.clone(),
+                    span,
+                }),
+                Node::Binary(op, ..) => {
+                    let rhs = built.pop().unwrap();
+                    let lhs = built.pop().unwrap();
+                    op.build(lhs, rhs, span)
+                }
+            };
+            built.push(exp);
+            Ok(())
+        });
+        done.unwrap();
+        built.pop().unwrap()
+    }
+
+    // Calls visit on every node, children before their parent and lhs before rhs, stopping at the first error
+    // Uses a stack of its own rather than recursing, so the depth of the tree doesn't matter
+    fn post_order<E>(&self, mut visit: impl FnMut(NodeId) -> Result<(), E>) -> Result<(), E> {
+        let mut tasks = vec![(self.root, false)]; // Whether the node's operands have been visited
+        while let Some((id, done)) = tasks.pop() {
+            match self.node(id) {
+                Node::Binary(_, lhs, rhs) if !done => {
+                    tasks.push((id, true));
+                    tasks.push((*rhs, false));
+                    tasks.push((*lhs, false));
+                }
+                _ => visit(id)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, node: Node, span: Span) -> NodeId {
+        let id = NodeId(u32::try_from(self.nodes.len()).expect("more than 2^32 nodes"));
+        self.nodes.push(node);
+        self.spans.push(span);
+        id
+    }
+}
+
+// What to_string prints for the same tree
+impl std::fmt::Display for Ast {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        enum Piece {
+            Node(NodeId),
+            Text(&'static str),
+        }
+        let mut pieces = vec![Piece::Node(self.root)];
+        while let Some(piece) = pieces.pop() {
+            match piece {
+                Piece::Text(text) => write!(f, "{}", text)?,
+                Piece::Node(id) => match self.node(id) {
+                    Node::Int(n) => write!(f, "{}", n)?,
+                    Node::BigInt(n) => write!(f, "{}", n)?,
+                    Node::Float(x) => write!(f, "{:?}", x)?,
+                    Node::Var(name) => write!(f, "{}", name)?,
+                    Node::Binary(op, lhs, rhs) => {
+                        write!(f, "({} ", op.symbol())?;
+                        pieces.push(Piece::Text(")"));
+                        pieces.push(Piece::Node(*rhs));
+                        pieces.push(Piece::Text(" "));
+                        pieces.push(Piece::Node(*lhs));
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+// Parses tokens from parser::lex as a single expression
+pub fn try_parse(ts: Vec<Token>) -> Result<Ast, ParseError> {
+    let mut parser = Parser {
+        eof: parser::eof_span(&ts),
+        toks: &ts,
+        pos: 0,
+        operators: OperatorTable::default(),
+        args: vec![],
+        ast: Ast {
+            nodes: Vec::with_capacity(ts.len()),
+            spans: Vec::with_capacity(ts.len()),
+            root: NodeId(0),
+        },
+    };
+    let root = parser.exp()?;
+    match parser.peek() {
+        None => {
+            let mut ast = parser.ast;
+            ast.root = root;
+            Ok(ast)
+        }
+        Some(tok) if tok.text == ")" => Err(ParseError::UnbalancedParen {
+            span: tok.span,
+            open: None,
+        }),
+        Some(tok) => Err(ParseError::TrailingTokens {
+            found: tok.text.to_string(),
+            span: tok.span,
+        }),
+    }
+}
+
+struct Parser<'t, 'a> {
+    toks: &'t [Token<'a>],
+    pos: usize, // The next token
+    eof: Span,
+    operators: OperatorTable,
+    args: Vec<NodeId>, // The operands of every paren form being parsed, innermost last
+    ast: Ast,
+}
+
+impl<'a> Parser<'_, 'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.toks.get(self.pos).copied()
+    }
+
+    fn exp(&mut self) -> Result<NodeId, ParseError> {
+        let tok = self
+            .peek()
+            .ok_or(ParseError::UnexpectedEof { span: self.eof })?;
+        self.pos += 1;
+        if let Some(op) = BinOp::from_symbol(tok.text) {
+            // Without parens an operator is always binary
+            let lhs = self.exp()?;
+            let rhs = self.exp()?;
+            let span = tok.span.to(self.ast.span(rhs));
+            return Ok(self.ast.push(Node::Binary(op, lhs, rhs), span));
+        }
+        match tok.text {
+            "(" => self.form(tok.span),
+            ")" => Err(ParseError::UnbalancedParen {
+                span: tok.span,
+                open: None,
+            }),
+            _ => {
+                let node = match Atoms::classify(tok)? {
+                    Atom::Int(digits) => match digits.parse() {
+                        Ok(n) => Node::Int(n),
+                        Err(_) => Node::BigInt(Atoms::int(digits, tok.span)?),
+                    },
+                    Atom::Float(x) => Node::Float(x),
+                    Atom::Name(name) => Node::Var(name.to_string()),
+                };
+                Ok(self.ast.push(node, tok.span))
+            }
+        }
+    }
+
+    // An operator and its operands, once the ( has been taken
+    fn form(&mut self, open: Span) -> Result<NodeId, ParseError> {
+        let (symbol, op_span) = match self.peek() {
+            None => {
+                return Err(ParseError::UnbalancedParen {
+                    span: self.eof,
+                    open: Some(open),
+                })
+            }
+            Some(tok) if tok.text == ")" => {
+                return Err(ParseError::EmptyParens {
+                    span: open.to(tok.span),
+                })
+            }
+            Some(tok) => (tok.text, tok.span),
+        };
+        let Some(op) = BinOp::from_symbol(symbol) else {
+            // Comparisons, keywords and calls have no arena form
+            return Err(ParseError::NotArithmetic {
+                found: symbol.to_string(),
+                span: op_span,
+            });
+        };
+        self.pos += 1;
+        let base = self.args.len();
+        loop {
+            match self.peek() {
+                None => {
+                    return Err(ParseError::UnbalancedParen {
+                        span: self.eof,
+                        open: Some(open),
+                    })
+                }
+                Some(tok) if tok.text == ")" => break,
+                Some(_) => {
+                    let arg = self.exp()?;
+                    self.args.push(arg);
+                }
+            }
+        }
+        let span = open.to(self.toks[self.pos].span);
+        self.pos += 1;
+        let args: Vec<NodeId> = self.args.drain(base..).collect();
+        let operator = self.operators.get(symbol).unwrap();
+        if args.is_empty() {
+            return Err(ParseError::MissingOperands {
+                op: symbol.to_string(),
+                span,
+            });
+        }
+        if args.len() < operator.min_arity {
+            if args.len() == 1 {
+                return Err(ParseError::UnaryNotAllowed {
+                    op: symbol.to_string(),
+                    span: op_span,
+                });
+            }
+            return Err(ParseError::WrongOperandCount {
+                op: symbol.to_string(),
+                expected: operator.min_arity,
+                found: args.len(),
+                span,
+            });
+        }
+        if args.len() > 1 {
+            return Ok(self.fold(op, operator.assoc, &args, span));
+        }
+        match operator.unary {
+            Unary::Identity => Ok(args[0]),
+            // (- 2) => (- 0 2), the literal wasn't written, so its span is empty
+            Unary::Lhs(n) => {
+                let lhs = self.ast.push(Node::Int(n), op_span.before());
+                Ok(self.ast.push(Node::Binary(op, lhs, args[0]), span))
+            }
+        }
+    }
+
+    // Like operators::fold, every node but the outermost covers just the operands folded into it
+    fn fold(&mut self, op: BinOp, assoc: Assoc, args: &[NodeId], span: Span) -> NodeId {
+        let last = args.len() - 1;
+        match assoc {
+            Assoc::Left => {
+                let mut id = args[0];
+                for (i, &arg) in args.iter().enumerate().skip(1) {
+                    let span = if i == last {
+                        span
+                    } else {
+                        self.ast.span(args[0]).to(self.ast.span(arg))
+                    };
+                    id = self.ast.push(Node::Binary(op, id, arg), span);
+                }
+                id
+            }
+            Assoc::Right => {
+                let mut id = args[last];
+                for (i, &arg) in args[..last].iter().enumerate().rev() {
+                    let span = if i == 0 {
+                        span
+                    } else {
+                        self.ast.span(arg).to(self.ast.span(args[last]))
+                    };
+                    id = self.ast.push(Node::Binary(op, arg, id), span);
+                }
+                id
+            }
+        }
+    }
+}
@@ -0,0 +1,182 @@
+// Functions written in Rust that expressions can call by name, like the ones they define themselves
+//   (max 1 7 3)      => 7
+//   (clamp 15 0 10)  => 10
+// The parser looks names up in a Builtins, so a host can add its own functions without touching the grammar:
+//   let mut builtins = Builtins::default();
+//   builtins.register("double", Arity::Exact(1), |args| Ok(Value::Num(args[0].to_num(Span::default())? * 2)));
+//   parser::try_parse_with(parser::lex("(double 21)"), &builtins)
+// A let, lambda or define that binds the same name hides the builtin, as it would any other name
+// So does a variable of the same name in the Env given to eval_with
+
+use crate::parser::{EvalError, Span};
+use crate::Value;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// How many arguments a builtin accepts
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Arity {
+    Exact(usize),
+    AtLeast(usize), // Variadic, min and max take any number of arguments but need at least one
+}
+
+impl Arity {
+    pub fn accepts(self, n: usize) -> bool {
+        match self {
+            Arity::Exact(expected) => n == expected,
+            Arity::AtLeast(min) => n >= min,
+        }
+    }
+}
+
+// 2 arguments, at least 1 argument
+impl std::fmt::Display for Arity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (prefix, n) = match self {
+            Arity::Exact(n) => ("", *n),
+            Arity::AtLeast(n) => ("at least ", *n),
+        };
+        write!(
+            f,
+            "{}{} argument{}",
+            prefix,
+            n,
+            if n == 1 { "" } else { "s" }
+        )
+    }
+}
+
+type Function = dyn Fn(&[Value]) -> Result<Value, EvalError>;
+
+pub struct Builtin {
+    pub name: String,
+    pub arity: Arity,
+    func: Box<Function>,
+}
+
+impl Builtin {
+    // Calls the function, which is only ever given as many arguments as its arity accepts
+    // Errors without a span are pointed at the call by the evaluator
+    pub fn call(&self, args: &[Value]) -> Result<Value, EvalError> {
+        (self.func)(args)
+    }
+}
+
+// Like closures, two builtins are only equal if they are the same one
+impl PartialEq for Builtin {
+    fn eq(&self, other: &Builtin) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Eq for Builtin {}
+
+impl std::hash::Hash for Builtin {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::ptr::hash(self, state);
+    }
+}
+
+impl std::fmt::Debug for Builtin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Builtin")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .finish_non_exhaustive()
+    }
+}
+
+// The registry the parser resolves names against
+// Builtins::new() is empty, Builtins::default() has min, max, abs, gcd and clamp
+#[derive(Clone, Debug)]
+pub struct Builtins {
+    functions: HashMap<String, Rc<Builtin>>,
+}
+
+impl Builtins {
+    pub fn new() -> Builtins {
+        Builtins {
+            functions: HashMap::new(),
+        }
+    }
+
+    // Adds a function, replacing any builtin already registered under name
+    // name should be a valid name that isn't a keyword, or there is no way to call it
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        arity: Arity,
+        func: impl Fn(&[Value]) -> Result<Value, EvalError> + 'static,
+    ) {
+        let name = name.into();
+        let builtin = Builtin {
+            name: name.clone(),
+            arity,
+            func: Box::new(func),
+        };
+        self.functions.insert(name, Rc::new(builtin));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Rc<Builtin>> {
+        self.functions.get(name)
+    }
+
+    // The names of every registered function, in no particular order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.functions.keys().map(String::as_str)
+    }
+}
+
+impl Default for Builtins {
+    fn default() -> Builtins {
+        let mut builtins = Builtins::new();
+        builtins.register("min", Arity::AtLeast(1), |args| {
+            Ok(Value::Num(nums(args)?.into_iter().min().unwrap()))
+        });
+        builtins.register("max", Arity::AtLeast(1), |args| {
+            Ok(Value::Num(nums(args)?.into_iter().max().unwrap()))
+        });
+        builtins.register("abs", Arity::Exact(1), |args| {
+            let n = nums(args)?[0];
+            n.checked_abs()
+                .map(Value::Num)
+                .ok_or_else(|| overflow("abs"))
+        });
+        // The greatest common divisor, which is never negative, and (gcd 0 0) => 0
+        builtins.register("gcd", Arity::AtLeast(1), |args| {
+            let gcd = nums(args)?
+                .into_iter()
+                .fold(0, |a, n| gcd(a, n.unsigned_abs()));
+            i64::try_from(gcd)
+                .map(Value::Num)
+                .map_err(|_| overflow("gcd"))
+        });
+        // (clamp x lo hi), lo wins when lo is greater than hi
+        builtins.register("clamp", Arity::Exact(3), |args| {
+            let [x, lo, hi] = nums(args)?[..] else {
+                unreachable!()
+            };
+            Ok(Value::Num(x.min(hi).max(lo)))
+        });
+        builtins
+    }
+}
+
+// The arguments as numbers, or a TypeMismatch for the first one that isn't
+fn nums(args: &[Value]) -> Result<Vec<i64>, EvalError> {
+    args.iter().map(|arg| arg.to_num(Span::default())).collect()
+}
+
+fn overflow(name: &str) -> EvalError {
+    EvalError::Overflow {
+        op: name.to_string(),
+        span: Span::default(),
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
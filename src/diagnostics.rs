@@ -0,0 +1,296 @@
+// Rustc-style error messages: the offending source line is printed and the part of it at fault is underlined
+//
+//   error: `^` cannot be unary
+//    --> 1:7
+//     |
+//   1 | (+ 1 (^ 1))
+//     |       ^ `^` cannot be unary
+
+use crate::parser::{EvalError, ParseError, Span};
+
+// A span of the source along with what to say about it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub primary: Label,        // Underlined with ^^^, this is where the error is
+    pub secondary: Vec<Label>, // Underlined with ---, extra context such as where a paren was opened
+    pub notes: Vec<String>,    // Printed after the source as "= note: ..."
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>, span: Span, label: impl Into<String>) -> Diagnostic {
+        Diagnostic {
+            message: message.into(),
+            primary: Label {
+                span,
+                message: label.into(),
+            },
+            secondary: vec![],
+            notes: vec![],
+        }
+    }
+
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Diagnostic {
+        self.secondary.push(Label {
+            span,
+            message: message.into(),
+        });
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Diagnostic {
+        self.notes.push(note.into());
+        self
+    }
+
+    // Renders the diagnostic against the source the spans point into
+    pub fn render(&self, source: &str) -> String {
+        let mut labels: Vec<(&Label, char)> = vec![(&self.primary, '^')];
+        labels.extend(self.secondary.iter().map(|label| (label, '-')));
+        labels.sort_by_key(|(label, _)| (label.span.line, label.span.col));
+
+        let last_line = labels
+            .iter()
+            .map(|(label, _)| label.span.line)
+            .max()
+            .unwrap_or(1);
+        let gutter = " ".repeat(last_line.to_string().len());
+
+        let mut out = format!("error: {}\n", self.message);
+        if self.primary.span.line == 0 {
+            // A default span doesn't point anywhere in the source (an ErrorExp for instance), so only the notes are left
+            for note in &self.notes {
+                out += &format!("  = note: {}\n", note);
+            }
+            return out;
+        }
+        out += &format!(
+            "{}--> {}:{}\n",
+            gutter, self.primary.span.line, self.primary.span.col
+        );
+        out += &format!("{} |\n", gutter);
+
+        let mut i = 0;
+        while i < labels.len() {
+            // Print each line once, followed by the underlines of every label on it
+            let line = labels[i].0.span.line;
+            let text = line_text(source, labels[i].0.span);
+            out += &format!("{:>width$} | {}\n", line, text, width = gutter.len());
+            while i < labels.len() && labels[i].0.span.line == line {
+                let (label, mark) = labels[i];
+                out += &format!("{} | {}\n", gutter, underline(source, label, mark));
+                i += 1;
+            }
+        }
+
+        if !self.notes.is_empty() {
+            out += &format!("{} |\n", gutter);
+        }
+        for note in &self.notes {
+            out += &format!("{} = note: {}\n", gutter, note);
+        }
+        out
+    }
+}
+
+// The full line of source that span starts on, without its line break
+fn line_text(source: &str, span: Span) -> &str {
+    let start = source[..span.start.min(source.len())]
+        .rfind('\n')
+        .map_or(0, |i| i + 1);
+    let end = source[start..]
+        .find('\n')
+        .map_or(source.len(), |i| start + i);
+    source[start..end].trim_end_matches('\r')
+}
+
+// Marks under the label's span, cut off at the end of the line, followed by the label's message
+fn underline(source: &str, label: &Label, mark: char) -> String {
+    let start = label.span.start.min(source.len());
+    let end = label.span.end.clamp(start, source.len());
+    let covered = source[start..end].split('\n').next().unwrap_or("");
+    let width = covered.chars().count().max(1); // Empty spans still get a single mark
+
+    let mut line = " ".repeat(label.span.col.saturating_sub(1));
+    line.extend(std::iter::repeat_n(mark, width));
+    if !label.message.is_empty() {
+        line += " ";
+        line += &label.message;
+    }
+    line
+}
+
+impl From<&ParseError> for Diagnostic {
+    fn from(err: &ParseError) -> Diagnostic {
+        let message = err.to_string();
+        match err {
+            ParseError::UnexpectedEof { span } => {
+                Diagnostic::error(message, *span, "expected an expression here")
+            }
+            ParseError::UnbalancedParen {
+                span,
+                open: Some(open),
+            } => Diagnostic::error(message, *span, "expected `)` before the end of the input")
+                .with_label(*open, "opened here"),
+            ParseError::UnbalancedParen { span, open: None } => {
+                Diagnostic::error(message, *span, "this `)` was never opened")
+            }
+            ParseError::EmptyParens { span } => {
+                Diagnostic::error(message, *span, "expected an operator inside")
+            }
+            ParseError::MissingOperands { op, span } => {
+                Diagnostic::error(message, *span, format!("`{}` has nothing to apply to", op))
+            }
+            ParseError::OperatorExpected { span, .. } => {
+                Diagnostic::error(message, *span, "expected an operator or a function")
+            }
+            // Which forms can be unary depends on the OperatorTable and Dialect, so there is no note saying which
+            ParseError::UnaryNotAllowed { op, span } => {
+                Diagnostic::error(message, *span, format!("`{}` cannot be unary", op))
+            }
+            ParseError::BadBinding { span, .. } => {
+                Diagnostic::error(message, *span, "expected `(name value)`").with_note(
+                    "let takes a list of bindings and then a body: (let ((x 1) (y 2)) (+ x y))",
+                )
+            }
+            ParseError::DuplicateBinding {
+                form, span, first, ..
+            } => {
+                let diagnostic = Diagnostic::error(message, *span, "bound again here")
+                    .with_label(*first, "first bound here");
+                if form == "let" {
+                    diagnostic.with_note(
+                        "let* binds names one after another, so a later binding can replace an earlier one",
+                    )
+                } else {
+                    diagnostic
+                }
+            }
+            ParseError::MisplacedDefine { span } => {
+                Diagnostic::error(message, *span, "not at the top level").with_note(
+                    "parse_program accepts defines, and let binds names inside an expression",
+                )
+            }
+            ParseError::BadDefine { span, .. } => {
+                Diagnostic::error(message, *span, "expected a name or `(name params...)`")
+            }
+            ParseError::BadParameter { span, .. } => {
+                Diagnostic::error(message, *span, "expected a name").with_note(
+                    "lambda takes a list of names and then a body: (lambda (x y) (+ x y))",
+                )
+            }
+            ParseError::WrongOperandCount { expected, span, .. } => Diagnostic::error(
+                message,
+                *span,
+                format!(
+                    "expected {} operand{}",
+                    expected,
+                    if *expected == 1 { "" } else { "s" }
+                ),
+            ),
+            ParseError::WrongArgumentCount { arity, span, .. } => {
+                Diagnostic::error(message, *span, format!("expected {}", arity))
+            }
+            ParseError::OperandExpected { span, .. } => {
+                Diagnostic::error(message, *span, "expected an operand")
+            }
+            ParseError::BareOperator { op, span } => {
+                Diagnostic::error(message, *span, format!("expected `({} ...)`", op))
+                    .with_note("this dialect only accepts operators inside parens")
+            }
+            ParseError::NotArithmetic { span, .. } => {
+                Diagnostic::error(message, *span, "expected one of + - * / % ^")
+                    .with_note("arena trees only hold numbers, names and arithmetic")
+            }
+            ParseError::TooDeep { span, .. } => {
+                Diagnostic::error(message, *span, "nested too deeply")
+                    .with_note("ParserOptions::dialect.max_depth sets the limit")
+            }
+            ParseError::ReservedWord { span, .. } => {
+                Diagnostic::error(message, *span, "expected a name or a number")
+                    .with_note("keywords can only come right after `(`")
+            }
+            ParseError::InvalidCharacter { span, .. } => {
+                Diagnostic::error(message, *span, "not a number, name or operator").with_note(
+                    "only numbers, names, whitespace, (, ), operators and keywords are valid input",
+                )
+            }
+            ParseError::TrailingTokens { span, .. } => {
+                Diagnostic::error(message, *span, "expected the end of the input")
+                    .with_note("wrap the expressions in an operator to combine them, or parse them with parse_program")
+            }
+            ParseError::LiteralOutOfRange { span, .. } => {
+                Diagnostic::error(message, *span, "too large for an f64")
+            }
+        }
+    }
+}
+
+impl From<&EvalError> for Diagnostic {
+    fn from(err: &EvalError) -> Diagnostic {
+        let message = err.to_string();
+        match err {
+            EvalError::Overflow { op, span } => Diagnostic::error(
+                message,
+                *span,
+                format!("the result of this `{}` is too large", op),
+            ),
+            EvalError::NegativeExponent { span } => {
+                Diagnostic::error(message, *span, "this exponent is negative")
+                    .with_note("eval_rational evaluates negative exponents exactly")
+            }
+            EvalError::FractionalExponent { span } => {
+                Diagnostic::error(message, *span, "this exponent isn't a whole number")
+                    .with_note("the result would not be rational")
+            }
+            EvalError::NotAnInteger { span, .. } => {
+                Diagnostic::error(message, *span, "this has a fraction")
+                    .with_note("eval_rational and eval_f64 accept any number")
+            }
+            EvalError::DivisionByZero { span } => {
+                Diagnostic::error(message, *span, "this divides by zero")
+            }
+            EvalError::LiteralOutOfRange { span, .. } => {
+                Diagnostic::error(message, *span, "too large to evaluate here")
+                    .with_note("eval_big evaluates literals of any length")
+            }
+            EvalError::TypeMismatch { found, span, .. } => {
+                Diagnostic::error(message, *span, format!("this is {}", found))
+            }
+            EvalError::ArityMismatch {
+                found,
+                span,
+                defined,
+                ..
+            } => Diagnostic::error(
+                message,
+                *span,
+                format!(
+                    "called with {} argument{}",
+                    found,
+                    if *found == 1 { "" } else { "s" }
+                ),
+            )
+            .with_label(*defined, "function defined here"),
+            EvalError::WrongArgumentCount { arity, span, .. } => {
+                Diagnostic::error(message, *span, format!("expected {}", arity))
+            }
+            EvalError::UnboundVariable { span, .. } => {
+                Diagnostic::error(message, *span, "not found in the environment")
+                    .with_note("eval_with looks names up in the Env it is given")
+            }
+            EvalError::FunctionNotSupported { span } => {
+                Diagnostic::error(message, *span, "this is a function")
+                    .with_note("functions work on i64s, with eval_value and eval_with")
+            }
+            EvalError::ErrorNode { span } => Diagnostic::error(message, *span, "")
+                .with_note("try_parse reports why the expression failed to parse"),
+        }
+    }
+}
@@ -0,0 +1,213 @@
+// The same trees as the Exp trait objects, as a closed enum that can be matched, compared and hashed
+//   let expr = Expr::from(&*parser::try_parse(lex("(+ 1 (* 2 x))"))?);
+//   match expr {
+//       Expr::Plus(lhs, rhs) => ...,
+//       ...
+//   }
+// Expr has no spans, so two trees are equal when they have the same shape whatever source they came from
+// expr.to_exp() goes back to an Rc<dyn Exp> for evaluating, with every span set to Span::default()
+
+use crate::builtins::Builtin;
+use crate::parser::{
+    AndExp, BinOp, BuiltinExp, CallExp, CmpExp, CmpOp, ErrorExp, Exp, FloatExp, IfExp, LambdaExp,
+    LetBinding, LetExp, LetKind, LitExp, Literal, Node, NotExp, OrExp, Param, Span, VarExp,
+};
+use crate::BigInt;
+use std::rc::Rc;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Expr {
+    Lit(BigInt),
+    Float(Float),
+    Var(String),
+    Builtin(Rc<Builtin>), // Equal to another builtin only if it is the same one
+    Plus(Box<Expr>, Box<Expr>),
+    Minus(Box<Expr>, Box<Expr>),
+    Mult(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>),
+    Mod(Box<Expr>, Box<Expr>),
+    Pow(Box<Expr>, Box<Expr>),
+    Compare(CmpOp, Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    If(Box<Expr>, Box<Expr>, Box<Expr>), // Condition, then, else
+    Let(LetKind, Vec<(String, Expr)>, Box<Expr>),
+    Lambda(Vec<String>, Box<Expr>),
+    Call(Box<Expr>, Vec<Expr>),
+    Error,
+}
+
+// A float literal, compared and hashed by its bits so that Expr can be Eq and Hash
+// NaN equals itself and 0.0 doesn't equal -0.0, which is what comparing trees wants
+#[derive(Clone, Copy, Debug)]
+pub struct Float(pub f64);
+
+impl PartialEq for Float {
+    fn eq(&self, other: &Float) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl Eq for Float {}
+
+impl std::hash::Hash for Float {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+
+impl Expr {
+    // lhs op rhs
+    pub fn binary(op: BinOp, lhs: Expr, rhs: Expr) -> Expr {
+        let (lhs, rhs) = (Box::new(lhs), Box::new(rhs));
+        match op {
+            BinOp::Plus => Expr::Plus(lhs, rhs),
+            BinOp::Minus => Expr::Minus(lhs, rhs),
+            BinOp::Mult => Expr::Mult(lhs, rhs),
+            BinOp::Div => Expr::Div(lhs, rhs),
+            BinOp::Mod => Expr::Mod(lhs, rhs),
+            BinOp::Pow => Expr::Pow(lhs, rhs),
+        }
+    }
+
+    // The tree as Exp trait objects, which is what evaluates and prints
+    pub fn to_exp(&self) -> Rc<dyn Exp> {
+        let span = Span::default();
+        let binary = |op: BinOp, lhs: &Expr, rhs: &Expr| op.build(lhs.to_exp(), rhs.to_exp(), span);
+        match self {
+            Expr::Lit(n) => Rc::new(LitExp { n: n.clone(), span }),
+            Expr::Float(x) => Rc::new(FloatExp { x: x.0, span }),
+            Expr::Var(name) => Rc::new(VarExp {
+                name: name.clone(),
+                span,
+            }),
+            Expr::Builtin(builtin) => Rc::new(BuiltinExp {
+                builtin: Rc::clone(builtin),
+                span,
+            }),
+            Expr::Plus(lhs, rhs) => binary(BinOp::Plus, lhs, rhs),
+            Expr::Minus(lhs, rhs) => binary(BinOp::Minus, lhs, rhs),
+            Expr::Mult(lhs, rhs) => binary(BinOp::Mult, lhs, rhs),
+            Expr::Div(lhs, rhs) => binary(BinOp::Div, lhs, rhs),
+            Expr::Mod(lhs, rhs) => binary(BinOp::Mod, lhs, rhs),
+            Expr::Pow(lhs, rhs) => binary(BinOp::Pow, lhs, rhs),
+            Expr::Compare(op, lhs, rhs) => Rc::new(CmpExp {
+                op: *op,
+                lhs: lhs.to_exp(),
+                rhs: rhs.to_exp(),
+                span,
+            }),
+            Expr::And(lhs, rhs) => Rc::new(AndExp {
+                lhs: lhs.to_exp(),
+                rhs: rhs.to_exp(),
+                span,
+            }),
+            Expr::Or(lhs, rhs) => Rc::new(OrExp {
+                lhs: lhs.to_exp(),
+                rhs: rhs.to_exp(),
+                span,
+            }),
+            Expr::Not(arg) => Rc::new(NotExp {
+                arg: arg.to_exp(),
+                span,
+            }),
+            Expr::If(cond, then, els) => Rc::new(IfExp {
+                cond: cond.to_exp(),
+                then: then.to_exp(),
+                els: els.to_exp(),
+                span,
+            }),
+            Expr::Let(kind, bindings, body) => Rc::new(LetExp {
+                sequential: *kind == LetKind::Sequential,
+                bindings: bindings
+                    .iter()
+                    .map(|(name, value)| LetBinding {
+                        name: name.clone(),
+                        value: value.to_exp(),
+                        span,
+                    })
+                    .collect(),
+                body: body.to_exp(),
+                span,
+            }),
+            Expr::Lambda(params, body) => Rc::new(LambdaExp {
+                params: params
+                    .iter()
+                    .map(|name| Param {
+                        name: name.clone(),
+                        span,
+                    })
+                    .collect(),
+                body: body.to_exp(),
+                span,
+            }),
+            Expr::Call(func, args) => Rc::new(CallExp {
+                func: func.to_exp(),
+                args: args.iter().map(Expr::to_exp).collect(),
+                span,
+            }),
+            Expr::Error => Rc::new(ErrorExp),
+        }
+    }
+}
+
+impl From<&dyn Exp> for Expr {
+    fn from(exp: &dyn Exp) -> Expr {
+        match exp.node() {
+            Node::Lit(Literal::Int(n)) => Expr::Lit(n.clone()),
+            Node::Lit(Literal::Float(x)) => Expr::Float(Float(x)),
+            Node::Var(name) => Expr::Var(name.to_string()),
+            Node::Builtin(builtin) => Expr::Builtin(Rc::clone(builtin)),
+            Node::Binary(op, lhs, rhs) => Expr::binary(op, Expr::from(lhs), Expr::from(rhs)),
+            Node::Compare(op, lhs, rhs) => {
+                Expr::Compare(op, Box::new(Expr::from(lhs)), Box::new(Expr::from(rhs)))
+            }
+            Node::And(lhs, rhs) => Expr::And(Box::new(Expr::from(lhs)), Box::new(Expr::from(rhs))),
+            Node::Or(lhs, rhs) => Expr::Or(Box::new(Expr::from(lhs)), Box::new(Expr::from(rhs))),
+            Node::Not(arg) => Expr::Not(Box::new(Expr::from(arg))),
+            Node::If(cond, then, els) => Expr::If(
+                Box::new(Expr::from(cond)),
+                Box::new(Expr::from(then)),
+                Box::new(Expr::from(els)),
+            ),
+            Node::Let(kind, bindings, body) => Expr::Let(
+                kind,
+                bindings
+                    .iter()
+                    .map(|b| (b.name.clone(), Expr::from(&*b.value)))
+                    .collect(),
+                Box::new(Expr::from(body)),
+            ),
+            Node::Lambda(params, body) => Expr::Lambda(
+                params.iter().map(|p| p.name.clone()).collect(),
+                Box::new(Expr::from(&**body)),
+            ),
+            Node::Call(func, args) => Expr::Call(
+                Box::new(Expr::from(func)),
+                args.iter().map(|arg| Expr::from(&**arg)).collect(),
+            ),
+            Node::Error => Expr::Error,
+        }
+    }
+}
+
+impl From<&Rc<dyn Exp>> for Expr {
+    fn from(exp: &Rc<dyn Exp>) -> Expr {
+        Expr::from(&**exp)
+    }
+}
+
+impl From<&Expr> for Rc<dyn Exp> {
+    fn from(expr: &Expr) -> Rc<dyn Exp> {
+        expr.to_exp()
+    }
+}
+
+// What to_string prints for the same tree, which parses back to it, except that a negative literal comes back as a
+// subtraction from 0
+impl std::fmt::Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_exp().to_string())
+    }
+}
@@ -0,0 +1,214 @@
+// Infix notation, parsed into the same trees as the Polish notation and printed back with as few parens as it needs
+//   1 + 2 * 3 ^ 2  => (+ 1 (* 2 (^ 3 2)))
+//   2 ^ 3 ^ 2      => (^ 2 (^ 3 2)), ^ is right associative and everything else is left associative
+//   -2 ^ 2         => (- 0 (^ 2 2)), unary minus binds tighter than * but not as tight as ^
+// Going the other way, to_infix((* (+ 1 2) 3)) => (1 + 2) * 3
+// Only arithmetic has an infix form, so comparisons, lets and the rest are left to the Polish notation
+
+use crate::parser::{
+    self, Atoms, BinOp, Exp, LitExp, Literal, MinusExp, Node, ParseError, Span, Token,
+};
+use crate::BigInt;
+use num_bigint::Sign;
+use std::rc::Rc;
+
+// Binding powers, higher binds tighter
+const UNARY: u8 = 3;
+
+fn precedence(op: BinOp) -> u8 {
+    match op {
+        BinOp::Plus | BinOp::Minus => 1,
+        BinOp::Mult | BinOp::Div | BinOp::Mod => 2,
+        BinOp::Pow => 4,
+    }
+}
+
+// Parses tokens from parser::lex as a single infix expression
+pub fn parse(ts: Vec<Token>) -> Result<Rc<dyn Exp>, ParseError> {
+    let mut parser = Parser {
+        eof: parser::eof_span(&ts),
+        toks: ts,
+        pos: 0,
+    };
+    let ast = parser.expr(0)?;
+    match parser.peek() {
+        None => Ok(ast),
+        Some(tok) if tok.text == ")" => Err(ParseError::UnbalancedParen {
+            span: tok.span,
+            open: None,
+        }),
+        Some(tok) => Err(ParseError::TrailingTokens {
+            found: tok.text.to_string(),
+            span: tok.span,
+        }),
+    }
+}
+
+struct Parser<'a> {
+    toks: Vec<Token<'a>>,
+    pos: usize, // The next token
+    eof: Span,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.toks.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Result<Token<'a>, ParseError> {
+        let tok = self
+            .peek()
+            .ok_or(ParseError::UnexpectedEof { span: self.eof })?;
+        self.pos += 1;
+        Ok(tok)
+    }
+
+    // Precedence climbing: an operand followed by every operator that binds at least as tightly as min
+    fn expr(&mut self, min: u8) -> Result<Rc<dyn Exp>, ParseError> {
+        let mut lhs = self.unary()?;
+        while let Some(op) = self.peek().and_then(|tok| BinOp::from_symbol(tok.text)) {
+            let prec = precedence(op);
+            if prec < min {
+                break;
+            }
+            self.pos += 1;
+            // The right operand of a left associative operator can't contain the same operator unparenthesized,
+            // which is what makes 1 - 2 - 3 fold to the left
+            let next = if op == BinOp::Pow { prec } else { prec + 1 };
+            let rhs = self.expr(next)?;
+            let span = lhs.span().to(rhs.span());
+            lhs = op.build(lhs, rhs, span);
+        }
+        Ok(lhs)
+    }
+
+    // -x, which is (- 0 x) like the Polish (- x)
+    fn unary(&mut self) -> Result<Rc<dyn Exp>, ParseError> {
+        match self.peek() {
+            Some(tok) if tok.text == "-" => {
+                self.pos += 1;
+                let operand = self.expr(UNARY)?;
+                Ok(Rc::new(MinusExp {
+                    lhs: Rc::new(LitExp {
+                        n: BigInt::from(0),
+                        span: tok.span.before(),
+                    }),
+                    span: tok.span.to(operand.span()),
+                    rhs: operand,
+                }))
+            }
+            _ => self.atom(),
+        }
+    }
+
+    // A number, a name, or a parenthesized expression
+    fn atom(&mut self) -> Result<Rc<dyn Exp>, ParseError> {
+        let tok = self.next()?;
+        if tok.text == "(" {
+            let inner = self.expr(0)?;
+            return match self.peek() {
+                Some(close) if close.text == ")" => {
+                    self.pos += 1;
+                    Ok(inner)
+                }
+                Some(other) => Err(ParseError::TrailingTokens {
+                    found: other.text.to_string(),
+                    span: other.span,
+                }),
+                None => Err(ParseError::UnbalancedParen {
+                    span: self.eof,
+                    open: Some(tok.span),
+                }),
+            };
+        }
+        if tok.text == ")" {
+            Err(ParseError::UnbalancedParen {
+                span: tok.span,
+                open: None,
+            })
+        } else if BinOp::from_symbol(tok.text).is_some() || tok.text.starts_with(['=', '<', '>']) {
+            Err(ParseError::OperandExpected {
+                found: tok.text.to_string(),
+                span: tok.span,
+            })
+        } else {
+            Atoms::parse(tok)
+        }
+    }
+}
+
+// exp in infix, with parens only where leaving them out would parse to a different tree
+// None if exp has something without an infix form, such as a comparison, a let or an ErrorExp
+pub fn to_infix(exp: &dyn Exp) -> Option<String> {
+    Some(print(exp)?.0)
+}
+
+// The text for exp along with the precedence of its outermost operator, which decides whether it needs parens
+fn print(exp: &dyn Exp) -> Option<(String, u8)> {
+    match exp.node() {
+        // Parsing never makes a negative literal, but a tree built from an Expr can have one, which prints like a
+        // unary minus so (^ -3 2) comes out as (-3) ^ 2
+        Node::Lit(Literal::Int(n)) => {
+            Some((n.to_string(), literal_precedence(n.sign() == Sign::Minus)))
+        }
+        Node::Lit(Literal::Float(x)) => {
+            Some((format!("{:?}", x), literal_precedence(x.is_sign_negative())))
+        }
+        Node::Var(name) => Some((name.to_string(), u8::MAX)),
+        Node::Builtin(builtin) => Some((builtin.name.clone(), u8::MAX)),
+        Node::Binary(BinOp::Minus, lhs, rhs) if is_unary(lhs) => {
+            let (text, prec) = print(rhs)?;
+            // Only operands that bind tighter go without parens, -(-x) reads better than --x
+            let text = if prec > UNARY {
+                format!("-{}", text)
+            } else {
+                format!("-({})", text)
+            };
+            Some((text, UNARY))
+        }
+        Node::Binary(op, lhs, rhs) => {
+            let prec = precedence(op);
+            let (left, left_prec) = print(lhs)?;
+            let (right, right_prec) = print(rhs)?;
+            // ^ groups to the right, so (2 ^ 3) ^ 2 needs its parens, while the others group to the left
+            // so 1 - (2 - 3) does; + and * need them too, since the tree would come back different without them
+            let left_parens = left_prec < prec || (left_prec == prec && op == BinOp::Pow);
+            let right_parens = right_prec < prec || (right_prec == prec && op != BinOp::Pow);
+            // A unary minus on the right can't be mistaken for anything else, 2 ^ -1
+            let right_parens = right_parens && right_prec != UNARY;
+            Some((
+                format!(
+                    "{} {} {}",
+                    parenthesize(left, left_parens),
+                    op.symbol(),
+                    parenthesize(right, right_parens)
+                ),
+                prec,
+            ))
+        }
+        _ => None,
+    }
+}
+
+fn literal_precedence(negative: bool) -> u8 {
+    if negative {
+        UNARY
+    } else {
+        u8::MAX
+    }
+}
+
+// The literal 0 that a unary minus puts on the left, which was never written so its span is empty
+fn is_unary(lhs: &dyn Exp) -> bool {
+    let zero = BigInt::from(0);
+    matches!(lhs.node(), Node::Lit(Literal::Int(n)) if *n == zero)
+        && lhs.span().start == lhs.span().end
+}
+
+fn parenthesize(text: String, parens: bool) -> String {
+    if parens {
+        format!("({})", text)
+    } else {
+        text
+    }
+}
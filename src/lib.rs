@@ -2,46 +2,412 @@
 // Project 0: Structs and Traits in Rust, Parsing/ASTs, S-expressions, Polish notation
 
 // A parser that parses an S-expression language for Polish notation and converts it into an AST using the structs defined in the lib.rs file
-// The language supports 4 artihmetic operations: +, -, *, ^ where arguments can be integers or other polish notation expressions
+// The language supports 6 artihmetic operations: +, -, *, /, %, ^ where arguments can be integers or other polish notation expressions
 // Parentheses are optional for binary operators, but are mandator in other k-ary instances
 
 // These are some examples of equivalent inputs:
 //   + 1 25 => (+ 1 25)
 //   - + 3 2 1 => (- (+ 3 2) 1)
-//   (- 3 2 1) => (- (- 3 2) 1) Note that operations +, -, and * are all left associative
+//   (- 3 2 1) => (- (- 3 2) 1) Note that operations +, -, *, / and % are all left associative
 //   (^ 3 2 1) => (^ 3 (^ 2 1)) Note that the operation ^ is right associative
 //   (- 2) => (- 0 2)
-//   (+ 3) => 3
+//   (+ 3) => 3, with ParserOptions::strict_spec(); try_parse builds (+ 0 3), which has the same value
 
-// Note that + and - support unary arguments, whereas * and ^ do not
+// Note that + and - support unary arguments, whereas *, /, % and ^ do not
+// Each operator is an entry in an operators::OperatorTable declaring its associativity, minimum number of operands and
+// what its unary form means, so ParserOptions can add operators or change these rules
+// ParserOptions::dialect turns off unary forms, bare binary operators or k-ary forms, and can limit how deeply
+// expressions nest
+
+// Comparisons, booleans and if always need parentheses:
+//   (< 1 2) => true, with = < <= > >= each taking exactly 2 operands
+//   (and (< 1 2) (= 1 2)) => false, and and or take 2 or more operands and stop at the first one that decides the result
+//   (not (< 1 2)) => false
+//   (if (< 2 10) (* 2 2) 2) => 4, only the branch that is picked gets evaluated
 
 //   Parse errors: return an ErrorExp struct
 //   e.g.,
-//   (* 4)     return ErrorExp, * and ^ cannot be used as unary operators
+//   (* 4)     return ErrorExp, *, /, % and ^ cannot be used as unary operators
 //   (^ 5)     ditto
 //   ()        return ErrorExp, parens cannot go alone
 //   (+)       return ErrorExp, operators must have 1 or more operands in all cases
-//   x         return ErrorExp, only numeric 0-9 characters, whitespace, (, ), +, -, *, ^, are valid input characters
+//   (if 1 2)  return ErrorExp, if takes exactly 3 operands
+//   $         return ErrorExp, only numbers, names, whitespace, (, ), operators and keywords are valid input
+//   (* 2x 3)  return ErrorExp, a number has to be followed by whitespace, a paren or an operator
+//   (+ if 1)  return ErrorExp, the keywords and, or, not, if cannot be used as names
+
+//   try_parse rejects the same inputs, but returns a ParseError saying why instead of an ErrorExp
+
+//   Evaluation errors: eval returns -1000000
+//   try_eval returns an EvalError instead, for ErrorExps, overflow, division by zero, and negative exponents
+//   eval_big evaluates with arbitrary-precision integers, so only ErrorExps, division by zero, and negative exponents fail
+//   eval_rational evaluates with exact rationals, so (^ 2 (- 3)) => 1/8
+//   eval_f64 evaluates with IEEE floating point, so (^ 2 0.5) => 1.4142135623730951
+//   eval_as::<N>() evaluates as any type implementing number::Number, such as i32, i128, or one of your own
+//   eval_value evaluates to a Value, which is an i64 or a boolean; try_eval and eval do the same but need a number
+//   The other evaluations only evaluate conditions as booleans, and fail on comparisons anywhere else
+
+// let binds names for its body, let* binds them one at a time so each value can use the names before it:
+//   (let ((x 3) (y 4)) (* x y)) => 12
+//   (let* ((x 3) (y (+ x 1))) (* x y)) => 12
+//   (let ((x 1) (x 2)) x) is an error, but let* allows it, and any let can shadow the names around it
+//   scope::shadowing finds the bindings that shadow another one
+
+// lambda makes a function, and a paren form starting with anything other than an operator calls one:
+//   ((lambda (x y) (* x y)) 3 4) => 12
+//   (let ((double (lambda (x) (* x 2)))) (double 21)) => 42
+//   Functions are closures, they keep the names that were in scope where the lambda was written
+//   (lambda (x x) x) is an error, and so is calling a function with the wrong number of arguments
+
+// parse_program parses any number of forms, which can define names for the forms after them:
+//   (define (square x) (* x x))
+//   (define n 12)
+//   (square n) => 144
+//   Defined names are visible everywhere, so functions can call themselves and each other
+//   define can only be used at the top level, so try_parse rejects it
+//   Calls in tail position don't use any stack, so a function can loop by calling itself as many times as it likes:
+//   (define (sum n acc) (if (= n 0) acc (sum (- n 1) (+ acc n))))
+//   (sum 1000000 0) => 500000500000
+
+// Builtin functions are called like any other: (max 1 7 3) => 7, (gcd 12 18) => 6, (clamp 15 0 10) => 10
+//   The parser resolves names against a Builtins registry, min, max, abs, gcd and clamp unless try_parse_with is given another
+//   Calls with the wrong number of arguments are parse errors, and a let, lambda or define of the same name hides a builtin
+//   So does a variable of the same name in the Env given to eval_with
+
+// Names such as x or row_2 are variables, eval_with looks them up in an Env and the other evaluations fail on them
+//   (* x (+ y 1)) with x = 2, y = 3 => 8
+
+// infix::parse reads the same arithmetic written infix, 1 + 2 * 3 ^ 2 => (+ 1 (* 2 (^ 3 2))), and infix::to_infix
+// writes any arithmetic tree back that way with only the parens it needs
+// rpn::parse and rpn::to_rpn do the same for reverse Polish notation, 1 2 + 3 * => (* (+ 1 2) 3)
+// pretty::Printer prints a tree too long for one line over several, breaking and indenting it to fit a width
+// and with resugar, prints the k-ary and unary forms the parser desugared, (+ (+ 1 2) 3) => (+ 1 2 3)
+// Expr::from(&*ast) converts a tree to a closed enum that can be matched, compared and hashed, and expr.to_exp()
+// converts it back
+// arena::try_parse parses arithmetic into one Vec of nodes, which evaluates without recursing however deep it is
+// Lexing and parsing take time linear in the length of the input, cargo bench --bench parse measures it on up to 10MB
+
+// Numbers are integers of any length, or floats written with a fraction or exponent: 1.5, 2e10, 1.5e-3
+// Integer evaluations (eval, try_eval, eval_big) only accept floats that are whole numbers
+
+pub use builtins::Builtins; // The functions the parser knows by name
+pub use expr::Expr; // The AST as an enum, for passes that match on it
+pub use num_bigint::BigInt; // The result of eval_big
+pub use rational::Rational; // The result of eval_rational
+pub use value::{Env, Value}; // The result of eval_value, and the variables eval_with can see
+
+pub mod arena; // Arithmetic trees kept in a Vec, for very large inputs
+pub mod builtins; // Functions written in Rust, such as min and max
+pub mod diagnostics; // Renders parse and evaluation errors against the source they came from
+pub mod expr; // The AST as a closed enum, converting to and from Exp trait objects
+pub mod infix; // Parsing and printing infix notation, 1 + 2 * 3
+pub mod number; // Evaluation over any numeric type
+pub mod operators; // The arithmetic operators and how their k-ary forms fold
+pub mod pretty; // Printing big trees over several lines
+pub mod rational;
+pub mod rpn; // Parsing and printing reverse Polish notation, 1 2 + 3 *
+pub mod scope; // Static checks on the names bound by let and lambda
+pub mod value; // Evaluation to numbers and booleans
 
 #[allow(dead_code)]
 #[allow(unused_variables)]
-
 pub mod parser {
+    use crate::builtins::{Arity, Builtin};
+    use crate::number::{self, Number};
+    use crate::operators::{self, Assoc, Operator, OperatorTable, Unary};
+    use crate::value;
+    use crate::{Builtins, Env, Rational, Value};
     use core::panic;
+    use num_bigint::BigInt;
     use regex::Regex;
     use std::error;
+    use std::sync::LazyLock;
 
     pub trait Exp {
         fn print(&self);
-        fn eval(&self) -> i32;
         fn to_string(&self) -> String;
         fn is_error(&self) -> bool;
+        fn span(&self) -> Span; // Where in the source this expression was parsed from
+        fn node(&self) -> Node<'_>; // What kind of expression this is, which is all evaluation needs to know
+
+        // Moves the children nothing else holds onto stack, leaving placeholders, so trees drop without recursing
+        fn take_children(&mut self, _stack: &mut Vec<std::rc::Rc<dyn Exp>>) {}
+
+        // Evaluates to a number or a boolean, with checked i64 arithmetic
+        fn eval_value(&self) -> Result<Value, EvalError> {
+            self.eval_with(&Env::new())
+        }
+
+        // Evaluates like eval_value, looking variables up in env
+        fn eval_with(&self, env: &Env) -> Result<Value, EvalError> {
+            value::eval_node(self.node(), self.span(), env)
+        }
+
+        // Evaluates with checked i64 arithmetic, failing if the result is a boolean
+        fn try_eval(&self) -> Result<i64, EvalError> {
+            self.eval_value()?.to_num(self.span())
+        }
+
+        // Evaluates with arbitrary-precision integers
+        fn eval_big(&self) -> Result<BigInt, EvalError> {
+            number::eval_node(self.node(), self.span())
+        }
+
+        // Evaluates with exact rationals
+        fn eval_rational(&self) -> Result<Rational, EvalError> {
+            number::eval_node(self.node(), self.span())
+        }
+
+        // Evaluates with IEEE floating point
+        fn eval_f64(&self) -> Result<f64, EvalError> {
+            number::eval_node(self.node(), self.span())
+        }
+
+        fn eval(&self) -> i32 {
+            // Errors, including results that don't fit in an i32, evaluate to -1000000; use try_eval to tell them apart
+            match self.try_eval() {
+                Ok(n) => i32::try_from(n).unwrap_or(-1000000),
+                Err(_) => -1000000,
+            }
+        }
+    }
+
+    impl dyn Exp {
+        // Evaluates as any Number, ast.eval_as::<i128>() for instance
+        // Only arithmetic, conditions and lets, a lambda, call or builtin is a FunctionNotSupported, as it is
+        // for eval_big, eval_rational and eval_f64
+        pub fn eval_as<N: Number>(&self) -> Result<N, EvalError> {
+            number::eval(self)
+        }
+    }
+
+    // The shape of an expression, as returned by Exp::node
+    #[derive(Clone, Copy)]
+    pub enum Node<'a> {
+        Lit(Literal<'a>),
+        Var(&'a str),
+        Binary(BinOp, &'a dyn Exp, &'a dyn Exp),
+        Compare(CmpOp, &'a dyn Exp, &'a dyn Exp),
+        And(&'a dyn Exp, &'a dyn Exp),
+        Or(&'a dyn Exp, &'a dyn Exp),
+        Not(&'a dyn Exp),
+        If(&'a dyn Exp, &'a dyn Exp, &'a dyn Exp), // Condition, then, else
+        Let(LetKind, &'a [LetBinding], &'a dyn Exp),
+        Lambda(&'a [Param], &'a std::rc::Rc<dyn Exp>), // The body is shared with the closures made from it
+        Call(&'a dyn Exp, &'a [std::rc::Rc<dyn Exp>]),
+        Builtin(&'a std::rc::Rc<Builtin>), // A function from the Builtins the expression was parsed with
+        Error,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub enum BinOp {
+        Plus,
+        Minus,
+        Mult,
+        Div,
+        Mod,
+        Pow,
+    }
+
+    impl BinOp {
+        pub fn from_symbol(symbol: &str) -> Option<BinOp> {
+            match symbol {
+                "+" => Some(BinOp::Plus),
+                "-" => Some(BinOp::Minus),
+                "*" => Some(BinOp::Mult),
+                "/" => Some(BinOp::Div),
+                "%" => Some(BinOp::Mod),
+                "^" => Some(BinOp::Pow),
+                _ => None,
+            }
+        }
+
+        // The node for lhs op rhs, which was parsed from span
+        pub fn build(
+            self,
+            lhs: std::rc::Rc<dyn Exp>,
+            rhs: std::rc::Rc<dyn Exp>,
+            span: Span,
+        ) -> std::rc::Rc<dyn Exp> {
+            match self {
+                BinOp::Plus => std::rc::Rc::new(PlusExp { lhs, rhs, span }),
+                BinOp::Minus => std::rc::Rc::new(MinusExp { lhs, rhs, span }),
+                BinOp::Mult => std::rc::Rc::new(MultExp { lhs, rhs, span }),
+                BinOp::Div => std::rc::Rc::new(DivExp { lhs, rhs, span }),
+                BinOp::Mod => std::rc::Rc::new(ModExp { lhs, rhs, span }),
+                BinOp::Pow => std::rc::Rc::new(PowExp { lhs, rhs, span }),
+            }
+        }
+
+        pub fn symbol(self) -> &'static str {
+            match self {
+                BinOp::Plus => "+",
+                BinOp::Minus => "-",
+                BinOp::Mult => "*",
+                BinOp::Div => "/",
+                BinOp::Mod => "%",
+                BinOp::Pow => "^",
+            }
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub enum CmpOp {
+        Eq,
+        Lt,
+        Le,
+        Gt,
+        Ge,
+    }
+
+    impl CmpOp {
+        pub fn symbol(self) -> &'static str {
+            match self {
+                CmpOp::Eq => "=",
+                CmpOp::Lt => "<",
+                CmpOp::Le => "<=",
+                CmpOp::Gt => ">",
+                CmpOp::Ge => ">=",
+            }
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub enum LetKind {
+        Parallel,   // let, every value is evaluated outside the let
+        Sequential, // let*, each value can see the names bound before it
+    }
+
+    // A number as it was written, before being converted to whatever it's evaluated as
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum Literal<'a> {
+        Int(&'a BigInt), // 12
+        Float(f64),      // 1.5, 2e10
+    }
+
+    impl Literal<'_> {
+        // The literal as source that parses back to it, or to (- 0 3) for a -3 in a tree built from an Expr, since
+        // the lexer reads - as an operator rather than as part of a number
+        pub fn to_source(&self) -> String {
+            match self {
+                Literal::Int(n) if n.sign() == num_bigint::Sign::Minus => format!("(- 0 {})", -*n),
+                Literal::Float(x) if x.is_sign_negative() => format!("(- 0 {:?})", -x),
+                lit => lit.to_string(),
+            }
+        }
+    }
+
+    impl std::fmt::Display for Literal<'_> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Literal::Int(n) => write!(f, "{}", n),
+                // Debug keeps the fraction or exponent, so the literal lexes as a float again
+                Literal::Float(x) => write!(f, "{:?}", x),
+            }
+        }
+    }
+
+    // A region of the source text: byte offsets start..end, plus the 1-based line and column where it starts
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct Span {
+        pub start: usize,
+        pub end: usize,
+        pub line: usize,
+        pub col: usize,
+    }
+
+    impl Span {
+        // The span running from the start of self to the end of other
+        pub fn to(self, other: Span) -> Span {
+            Span {
+                end: other.end,
+                ..self
+            }
+        }
+
+        // An empty span sitting right before self, used for things that were never written in the source
+        pub fn before(self) -> Span {
+            Span {
+                end: self.start,
+                ..self
+            }
+        }
+    }
+
+    // One top-level form of a program
+    #[derive(Clone)]
+    pub enum Form {
+        // (define x 1), and (define (f x) body) which defines f as (lambda (x) body)
+        Define {
+            name: String,
+            value: std::rc::Rc<dyn Exp>,
+            span: Span,
+        },
+        Exp(std::rc::Rc<dyn Exp>),
+    }
+
+    // The forms parsed by parse_program, which run in order
+    #[derive(Clone)]
+    pub struct Program {
+        pub forms: Vec<Form>,
+    }
+
+    impl Program {
+        // The value of every expression in the program, defines don't have one
+        pub fn eval_all(&self) -> Result<Vec<Value>, EvalError> {
+            self.eval_all_with(&Env::new())
+        }
+
+        pub fn eval_all_with(&self, env: &Env) -> Result<Vec<Value>, EvalError> {
+            value::eval_program(&self.forms, env)
+        }
+
+        // The value of the last expression, or None if the program only defines things
+        pub fn eval(&self) -> Result<Option<Value>, EvalError> {
+            self.eval_with(&Env::new())
+        }
+
+        pub fn eval_with(&self, env: &Env) -> Result<Option<Value>, EvalError> {
+            Ok(self.eval_all_with(env)?.pop())
+        }
+    }
+
+    impl std::fmt::Display for Form {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Form::Define { name, value, .. } => {
+                    write!(f, "(define {} {})", name, value.to_string())
+                }
+                Form::Exp(exp) => write!(f, "{}", exp.to_string()),
+            }
+        }
+    }
+
+    // One form per line
+    impl std::fmt::Display for Program {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            for (i, form) in self.forms.iter().enumerate() {
+                if i > 0 {
+                    writeln!(f)?;
+                }
+                write!(f, "{}", form)?;
+            }
+            Ok(())
+        }
+    }
+
+    // A token produced by lex: the text it was made from and where that text is in the source
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Token<'a> {
+        pub text: &'a str,
+        pub span: Span,
     }
 
     #[derive(Clone)]
     pub struct PlusExp {
         pub lhs: std::rc::Rc<dyn Exp>,
         pub rhs: std::rc::Rc<dyn Exp>,
+        pub span: Span,
     }
 
     impl Exp for PlusExp {
@@ -57,29 +423,72 @@ pub mod parser {
             }
         }
 
-        fn eval(&self) -> i32 {
-            if self.lhs.is_error() || self.rhs.is_error() {
-                -1000000
-            } else {
-                self.lhs.eval() + self.rhs.eval()
-            }
-        }
-
         fn to_string(&self) -> String {
             if self.lhs.is_error() || self.rhs.is_error() {
-                format!("error")
+                "error".to_string()
             } else {
                 format!("(+ {} {})", self.lhs.to_string(), self.rhs.to_string())
             }
         }
 
         fn is_error(&self) -> bool {
-            if self.lhs.is_error() || self.rhs.is_error() {
-                true
-            } else {
-                false
+            self.lhs.is_error() || self.rhs.is_error()
+        }
+
+        fn span(&self) -> Span {
+            self.span
+        }
+
+        fn node(&self) -> Node<'_> {
+            Node::Binary(BinOp::Plus, &*self.lhs, &*self.rhs)
+        }
+
+        fn take_children(&mut self, stack: &mut Vec<std::rc::Rc<dyn Exp>>) {
+            take(&mut self.lhs, stack);
+            take(&mut self.rhs, stack);
+        }
+    }
+
+    // A tree can be far deeper than the stack: (+ 1 1 ...) with a million operands folds into a chain of a million
+    // PlusExps, and dropping the fields of each node in turn would take a stack frame per level
+    // Dropping a node takes its children instead, and the children of those, and drops them one at a time from a Vec
+    macro_rules! drop_iteratively {
+        ($($t:ty),*) => {$(
+            impl Drop for $t {
+                fn drop(&mut self) {
+                    let mut stack = vec![];
+                    self.take_children(&mut stack);
+                    while let Some(mut exp) = stack.pop() {
+                        if let Some(exp) = std::rc::Rc::get_mut(&mut exp) {
+                            exp.take_children(&mut stack);
+                        }
+                    }
+                }
             }
+        )*};
+    }
+
+    drop_iteratively!(
+        PlusExp, MinusExp, MultExp, PowExp, DivExp, ModExp, CmpExp, AndExp, OrExp, NotExp, IfExp,
+        LetExp, LambdaExp, CallExp
+    );
+
+    // Moves exp onto stack if nothing else holds it, leaving behind a placeholder that is cheap to drop
+    fn take(exp: &mut std::rc::Rc<dyn Exp>, stack: &mut Vec<std::rc::Rc<dyn Exp>>) {
+        if std::rc::Rc::get_mut(exp).is_some() {
+            stack.push(std::mem::replace(exp, placeholder()));
+        }
+    }
+
+    // One shared ErrorExp, so taking a child doesn't allocate
+    fn placeholder() -> std::rc::Rc<dyn Exp> {
+        thread_local! {
+            static PLACEHOLDER: std::rc::Rc<dyn Exp> = std::rc::Rc::new(ErrorExp);
         }
+        // A tree dropped while the thread is exiting can outlive the thread local
+        PLACEHOLDER
+            .try_with(std::rc::Rc::clone)
+            .unwrap_or_else(|_| std::rc::Rc::new(ErrorExp))
     }
 
     #[derive(Clone)]
@@ -89,19 +498,392 @@ pub mod parser {
             println!("Error");
         }
 
-        fn eval(&self) -> i32 {
-            -1000000
-        }
-
         fn to_string(&self) -> String {
-            format!("error")
+            "error".to_string()
         }
 
         fn is_error(&self) -> bool {
             true
         }
+
+        fn span(&self) -> Span {
+            Span::default() // An ErrorExp doesn't come from any particular part of the source
+        }
+
+        fn node(&self) -> Node<'_> {
+            Node::Error
+        }
+    }
+
+    // The reason a parse failed, as returned by try_parse, along with where it happened
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub enum ParseError {
+        // The input ended where an expression was expected
+        UnexpectedEof {
+            span: Span,
+        },
+        // A ) that was never opened, or a ( that was never closed, in which case open is where it was opened
+        UnbalancedParen {
+            span: Span,
+            open: Option<Span>,
+        },
+        // ()
+        EmptyParens {
+            span: Span,
+        },
+        // (+)
+        MissingOperands {
+            op: String,
+            span: Span,
+        },
+        // (1 2)
+        OperatorExpected {
+            found: String,
+            span: Span,
+        },
+        // (^ 5)
+        UnaryNotAllowed {
+            op: String,
+            span: Span,
+        },
+        // (+ if 1)
+        ReservedWord {
+            word: String,
+            span: Span,
+        },
+        // (let (x 1) x), (let ((x)) x)
+        BadBinding {
+            found: String,
+            span: Span,
+        },
+        // (let ((x 1) (x 2)) x), first is where name was bound the first time
+        DuplicateBinding {
+            form: String, // let or lambda
+            name: String,
+            span: Span,
+            first: Span,
+        },
+        // (lambda x x), (lambda (1) 1)
+        BadParameter {
+            found: String,
+            span: Span,
+        },
+        // (+ 1 (define x 2)), or any define given to try_parse rather than parse_program
+        MisplacedDefine {
+            span: Span,
+        },
+        // (define 1 2)
+        BadDefine {
+            found: String,
+            span: Span,
+        },
+        // (< 1 2 3), (if 1 2)
+        WrongOperandCount {
+            op: String,
+            expected: usize,
+            found: usize,
+            span: Span,
+        },
+        // (abs 1 2), a builtin called with a number of arguments its arity doesn't accept
+        WrongArgumentCount {
+            name: String,
+            arity: Arity,
+            found: usize,
+            span: Span,
+        },
+        // 1 + * 2 in infix, where an operand has to come before or after each operator
+        OperandExpected {
+            found: String,
+            span: Span,
+        },
+        // + 1 2, in a dialect where operators need parens
+        BareOperator {
+            op: String,
+            span: Span,
+        },
+        // (< 1 2) for the arena, which only holds arithmetic, found is what came after the paren
+        NotArithmetic {
+            found: String,
+            span: Span,
+        },
+        // Paren forms and operators nested more than max deep, span is the one that went past the limit
+        TooDeep {
+            max: usize,
+            span: Span,
+        },
+        // x
+        InvalidCharacter {
+            found: String,
+            span: Span,
+        },
+        // + 1 2 3
+        TrailingTokens {
+            found: String,
+            span: Span,
+        },
+        // A float literal too large for an f64
+        LiteralOutOfRange {
+            literal: String,
+            span: Span,
+        },
+    }
+
+    impl ParseError {
+        pub fn span(&self) -> Span {
+            match self {
+                ParseError::UnexpectedEof { span }
+                | ParseError::UnbalancedParen { span, .. }
+                | ParseError::EmptyParens { span }
+                | ParseError::MissingOperands { span, .. }
+                | ParseError::OperatorExpected { span, .. }
+                | ParseError::UnaryNotAllowed { span, .. }
+                | ParseError::WrongOperandCount { span, .. }
+                | ParseError::WrongArgumentCount { span, .. }
+                | ParseError::OperandExpected { span, .. }
+                | ParseError::BareOperator { span, .. }
+                | ParseError::NotArithmetic { span, .. }
+                | ParseError::TooDeep { span, .. }
+                | ParseError::ReservedWord { span, .. }
+                | ParseError::BadBinding { span, .. }
+                | ParseError::DuplicateBinding { span, .. }
+                | ParseError::BadParameter { span, .. }
+                | ParseError::MisplacedDefine { span }
+                | ParseError::BadDefine { span, .. }
+                | ParseError::InvalidCharacter { span, .. }
+                | ParseError::TrailingTokens { span, .. }
+                | ParseError::LiteralOutOfRange { span, .. } => *span,
+            }
+        }
+    }
+
+    impl std::fmt::Display for ParseError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                ParseError::UnexpectedEof { .. } => write!(f, "unexpected end of input"),
+                ParseError::UnbalancedParen { .. } => write!(f, "unbalanced parenthesis"),
+                ParseError::EmptyParens { .. } => write!(f, "parens cannot go alone"),
+                ParseError::MissingOperands { op, .. } => {
+                    write!(f, "`{}` needs at least one operand", op)
+                }
+                ParseError::OperatorExpected { found, .. } => {
+                    write!(
+                        f,
+                        "expected an operator or a function after `(`, found `{}`",
+                        found
+                    )
+                }
+                ParseError::UnaryNotAllowed { op, .. } => write!(f, "`{}` cannot be unary", op),
+                ParseError::ReservedWord { word, .. } => {
+                    write!(f, "`{}` is a keyword and cannot be used as a name", word)
+                }
+                ParseError::BadBinding { found, .. } => {
+                    write!(f, "expected a binding like `(x 1)`, found `{}`", found)
+                }
+                ParseError::DuplicateBinding { form, name, .. } => {
+                    write!(f, "`{}` is bound twice in the same `{}`", name, form)
+                }
+                ParseError::MisplacedDefine { .. } => {
+                    write!(f, "`define` can only be used at the top level of a program")
+                }
+                ParseError::BadDefine { found, .. } => {
+                    write!(f, "expected a name to define, found `{}`", found)
+                }
+                ParseError::BadParameter { found, .. } => {
+                    write!(f, "expected a parameter name, found `{}`", found)
+                }
+                ParseError::WrongOperandCount {
+                    op,
+                    expected,
+                    found,
+                    ..
+                } => write!(
+                    f,
+                    "`{}` takes {} operand{}, found {}",
+                    op,
+                    expected,
+                    if *expected == 1 { "" } else { "s" },
+                    found
+                ),
+                ParseError::WrongArgumentCount {
+                    name, arity, found, ..
+                } => write!(f, "`{}` takes {}, found {}", name, arity, found),
+                ParseError::OperandExpected { found, .. } => {
+                    write!(f, "expected a number, a name or `(`, found `{}`", found)
+                }
+                ParseError::BareOperator { op, .. } => {
+                    write!(f, "`{}` needs parentheses around it and its operands", op)
+                }
+                ParseError::NotArithmetic { found, .. } => {
+                    write!(
+                        f,
+                        "expected an arithmetic operator after `(`, found `{}`",
+                        found
+                    )
+                }
+                ParseError::TooDeep { max, .. } => {
+                    write!(f, "expressions cannot be nested more than {} deep", max)
+                }
+                ParseError::InvalidCharacter { found, .. } => {
+                    write!(f, "invalid character in `{}`", found)
+                }
+                ParseError::TrailingTokens { found, .. } => {
+                    write!(f, "unexpected `{}` after the expression", found)
+                }
+                ParseError::LiteralOutOfRange { literal, .. } => {
+                    write!(f, "literal `{}` is out of range", literal)
+                }
+            }
+        }
+    }
+
+    impl error::Error for ParseError {}
+
+    // The reason an evaluation failed, as returned by try_eval, along with the expression that failed
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub enum EvalError {
+        // The result of op doesn't fit in the type being evaluated to
+        Overflow {
+            op: String,
+            span: Span,
+        },
+        // (^ 2 (- 1)), span is the exponent
+        NegativeExponent {
+            span: Span,
+        },
+        // (^ 2 (^ 2 (- 1))) has no exact result, span is the exponent
+        FractionalExponent {
+            span: Span,
+        },
+        // (/ 1 0), (% 1 0), (^ 0 (- 1))
+        DivisionByZero {
+            span: Span,
+        },
+        // A literal that doesn't fit in the type being evaluated to
+        LiteralOutOfRange {
+            literal: String,
+            span: Span,
+        },
+        // A float literal with a fraction, in an integer evaluation
+        NotAnInteger {
+            literal: String,
+            span: Span,
+        },
+        // (+ 1 (< 1 2)), (if 1 2 3), span is the operand of the wrong type
+        TypeMismatch {
+            expected: String,
+            found: String,
+            span: Span,
+        },
+        // ((lambda (x) x) 1 2), defined is where the function was written
+        ArityMismatch {
+            expected: usize,
+            found: usize,
+            span: Span,
+            defined: Span,
+        },
+        // (let ((f abs)) (f 1 2)), a builtin called with a number of arguments its arity doesn't accept
+        WrongArgumentCount {
+            name: String,
+            arity: Arity,
+            found: usize,
+            span: Span,
+        },
+        // A name that isn't in the Env
+        UnboundVariable {
+            name: String,
+            span: Span,
+        },
+        // A lambda, call or builtin in an evaluation over a Number, which only eval_value supports
+        FunctionNotSupported {
+            span: Span,
+        },
+        // The tree contains an ErrorExp
+        ErrorNode {
+            span: Span,
+        },
+    }
+
+    impl EvalError {
+        pub fn span(&self) -> Span {
+            match self {
+                EvalError::Overflow { span, .. }
+                | EvalError::NegativeExponent { span }
+                | EvalError::FractionalExponent { span }
+                | EvalError::DivisionByZero { span }
+                | EvalError::LiteralOutOfRange { span, .. }
+                | EvalError::NotAnInteger { span, .. }
+                | EvalError::TypeMismatch { span, .. }
+                | EvalError::UnboundVariable { span, .. }
+                | EvalError::ArityMismatch { span, .. }
+                | EvalError::WrongArgumentCount { span, .. }
+                | EvalError::FunctionNotSupported { span }
+                | EvalError::ErrorNode { span } => *span,
+            }
+        }
+
+        // The same error pointing at span instead
+        pub fn with_span(mut self, at: Span) -> EvalError {
+            match &mut self {
+                EvalError::Overflow { span, .. }
+                | EvalError::NegativeExponent { span }
+                | EvalError::FractionalExponent { span }
+                | EvalError::DivisionByZero { span }
+                | EvalError::LiteralOutOfRange { span, .. }
+                | EvalError::NotAnInteger { span, .. }
+                | EvalError::TypeMismatch { span, .. }
+                | EvalError::UnboundVariable { span, .. }
+                | EvalError::ArityMismatch { span, .. }
+                | EvalError::WrongArgumentCount { span, .. }
+                | EvalError::FunctionNotSupported { span }
+                | EvalError::ErrorNode { span } => *span = at,
+            }
+            self
+        }
+    }
+
+    impl std::fmt::Display for EvalError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                EvalError::Overflow { op, .. } => write!(f, "`{}` overflowed", op),
+                EvalError::NegativeExponent { .. } => write!(f, "negative exponent"),
+                EvalError::FractionalExponent { .. } => write!(f, "fractional exponent"),
+                EvalError::DivisionByZero { .. } => write!(f, "division by zero"),
+                EvalError::LiteralOutOfRange { literal, .. } => {
+                    write!(f, "literal `{}` is out of range", literal)
+                }
+                EvalError::NotAnInteger { literal, .. } => {
+                    write!(f, "`{}` is not an integer", literal)
+                }
+                EvalError::TypeMismatch {
+                    expected, found, ..
+                } => write!(f, "expected {}, found {}", expected, found),
+                EvalError::ArityMismatch {
+                    expected, found, ..
+                } => write!(
+                    f,
+                    "expected {} argument{}, found {}",
+                    expected,
+                    if *expected == 1 { "" } else { "s" },
+                    found
+                ),
+                EvalError::WrongArgumentCount {
+                    name, arity, found, ..
+                } => write!(f, "`{}` takes {}, found {}", name, arity, found),
+                EvalError::UnboundVariable { name, .. } => {
+                    write!(f, "unbound variable `{}`", name)
+                }
+                EvalError::FunctionNotSupported { .. } => {
+                    write!(f, "functions can only be evaluated by eval_value")
+                }
+                EvalError::ErrorNode { .. } => {
+                    write!(f, "cannot evaluate an expression that failed to parse")
+                }
+            }
+        }
     }
 
+    impl error::Error for EvalError {}
+
     // ---------------------------------------------------------------------------------------------------------------------
 
     //TODO: Implement MinusExp, MultExp, PowExp, LitExp and anything else you may need here
@@ -114,6 +896,7 @@ pub mod parser {
     pub struct MinusExp {
         pub lhs: std::rc::Rc<dyn Exp>,
         pub rhs: std::rc::Rc<dyn Exp>,
+        pub span: Span,
     }
 
     impl Exp for MinusExp {
@@ -125,17 +908,9 @@ pub mod parser {
             println!(")");
         }
 
-        fn eval(&self) -> i32 {
-            if self.lhs.is_error() || self.rhs.is_error() {
-                -1000000
-            } else {
-                self.lhs.eval() - self.rhs.eval() //changed to -
-            }
-        }
-
         fn to_string(&self) -> String {
             if self.lhs.is_error() || self.rhs.is_error() {
-                format!("error")
+                "error".to_string()
             } else {
                 format!("(- {} {})", self.lhs.to_string(), self.rhs.to_string())
                 // changed to -
@@ -143,11 +918,20 @@ pub mod parser {
         }
 
         fn is_error(&self) -> bool {
-            if self.lhs.is_error() || self.rhs.is_error() {
-                true
-            } else {
-                false
-            }
+            self.lhs.is_error() || self.rhs.is_error()
+        }
+
+        fn span(&self) -> Span {
+            self.span
+        }
+
+        fn node(&self) -> Node<'_> {
+            Node::Binary(BinOp::Minus, &*self.lhs, &*self.rhs)
+        }
+
+        fn take_children(&mut self, stack: &mut Vec<std::rc::Rc<dyn Exp>>) {
+            take(&mut self.lhs, stack);
+            take(&mut self.rhs, stack);
         }
     }
 
@@ -155,6 +939,7 @@ pub mod parser {
     pub struct MultExp {
         pub lhs: std::rc::Rc<dyn Exp>,
         pub rhs: std::rc::Rc<dyn Exp>,
+        pub span: Span,
     }
 
     impl Exp for MultExp {
@@ -166,13 +951,9 @@ pub mod parser {
             println!(")");
         }
 
-        fn eval(&self) -> i32 {
-            self.lhs.eval() * self.rhs.eval()
-        }
-
         fn to_string(&self) -> String {
             if self.lhs.is_error() || self.rhs.is_error() {
-                format!("error")
+                "error".to_string()
             } else {
                 format!("(* {} {})", self.lhs.to_string(), self.rhs.to_string())
                 // changed to *
@@ -180,11 +961,20 @@ pub mod parser {
         }
 
         fn is_error(&self) -> bool {
-            if self.lhs.is_error() || self.rhs.is_error() {
-                true
-            } else {
-                false
-            }
+            self.lhs.is_error() || self.rhs.is_error()
+        }
+
+        fn span(&self) -> Span {
+            self.span
+        }
+
+        fn node(&self) -> Node<'_> {
+            Node::Binary(BinOp::Mult, &*self.lhs, &*self.rhs)
+        }
+
+        fn take_children(&mut self, stack: &mut Vec<std::rc::Rc<dyn Exp>>) {
+            take(&mut self.lhs, stack);
+            take(&mut self.rhs, stack);
         }
     }
 
@@ -192,6 +982,7 @@ pub mod parser {
     pub struct PowExp {
         pub lhs: std::rc::Rc<dyn Exp>,
         pub rhs: std::rc::Rc<dyn Exp>,
+        pub span: Span,
     }
 
     impl Exp for PowExp {
@@ -203,19 +994,9 @@ pub mod parser {
             println!(")");
         }
 
-        fn eval(&self) -> i32 {
-            let base = self.lhs.eval();
-            let exponent = self.rhs.eval();
-            if exponent < 0 {
-                println!("Error: negative exponent");
-                return 1;
-            }
-            base.pow(exponent as u32)
-        }
-
         fn to_string(&self) -> String {
             if self.lhs.is_error() || self.rhs.is_error() {
-                format!("error")
+                "error".to_string()
             } else {
                 format!("(^ {} {})", self.lhs.to_string(), self.rhs.to_string())
             }
@@ -224,84 +1005,789 @@ pub mod parser {
         fn is_error(&self) -> bool {
             self.lhs.is_error() || self.rhs.is_error()
         }
+
+        fn span(&self) -> Span {
+            self.span
+        }
+
+        fn node(&self) -> Node<'_> {
+            Node::Binary(BinOp::Pow, &*self.lhs, &*self.rhs)
+        }
+
+        fn take_children(&mut self, stack: &mut Vec<std::rc::Rc<dyn Exp>>) {
+            take(&mut self.lhs, stack);
+            take(&mut self.rhs, stack);
+        }
     }
 
     #[derive(Clone)]
-    pub struct LitExp {
-        pub n: i32,
+    pub struct DivExp {
+        pub lhs: std::rc::Rc<dyn Exp>,
+        pub rhs: std::rc::Rc<dyn Exp>,
+        pub span: Span,
     }
 
-    impl Exp for LitExp {
+    impl Exp for DivExp {
         fn print(&self) {
-            print!("{}", self.n);
-        }
-
-        fn eval(&self) -> i32 {
-            self.n
+            print!("(/ ");
+            self.lhs.print();
+            print!(" ");
+            self.rhs.print();
+            print!(")");
         }
 
         fn to_string(&self) -> String {
-            format!("{}", self.n)
+            if self.lhs.is_error() || self.rhs.is_error() {
+                "error".to_string()
+            } else {
+                format!("(/ {} {})", self.lhs.to_string(), self.rhs.to_string())
+            }
         }
 
         fn is_error(&self) -> bool {
-            false
+            self.lhs.is_error() || self.rhs.is_error()
         }
-    }
 
-    // ---------------------------------------------------------------------------------------------------------------------
+        fn span(&self) -> Span {
+            self.span
+        }
 
-    pub fn lex(exp: &str) -> Vec<&str> {
-        /*
+        fn node(&self) -> Node<'_> {
+            Node::Binary(BinOp::Div, &*self.lhs, &*self.rhs)
+        }
 
-        */
-        let mut toks: Vec<&str> = Vec::new(); // We initialize an empty vector to store tokens
-        let mut last_index = 0;
-        let mut chars = exp.char_indices().peekable(); // We create an iterator over char indices
-
-        while let Some((i, c)) = chars.peek() {
-            // We iterate over characters
-            match c {
-                '+' | '-' | '*' | '^' | '(' | ')' => {
-                    // If we notice that there's a non-operator token, add it to the toks vector
-                    if *i > last_index {
-                        let tok = &exp[last_index..*i].trim();
-                        if !tok.is_empty() {
-                            toks.push(tok);
-                        }
-                    }
-                    // Here we are getting a reference from chars.peek thus we need to dereference
+        fn take_children(&mut self, stack: &mut Vec<std::rc::Rc<dyn Exp>>) {
+            take(&mut self.lhs, stack);
+            take(&mut self.rhs, stack);
+        }
+    }
 
-                    // Using *i dereferences the i reference to get the actual usize value (the index)
-                    toks.push(&exp[*i..*i + 1]);
-                    last_index = *i + 1;
-                }
-                '0'..='9' => (), // Continue if the character is a digit, we want to collect digits until we form a full number
-                _ => {
-                    if *i > last_index {
-                        let tok = &exp[last_index..*i].trim();
-                        if !tok.is_empty() {
-                            toks.push(tok);
-                        }
-                        last_index = *i + 1;
-                    }
-                }
-            }
-            chars.next();
+    #[derive(Clone)]
+    pub struct ModExp {
+        pub lhs: std::rc::Rc<dyn Exp>,
+        pub rhs: std::rc::Rc<dyn Exp>,
+        pub span: Span,
+    }
+
+    impl Exp for ModExp {
+        fn print(&self) {
+            print!("(% ");
+            self.lhs.print();
+            print!(" ");
+            self.rhs.print();
+            print!(")");
         }
 
-        // Add any remaining token at the end of the string such as ending ')'
-        if last_index < exp.len() {
-            let tok = &exp[last_index..exp.len()].trim();
-            if !tok.is_empty() {
-                toks.push(tok);
+        fn to_string(&self) -> String {
+            if self.lhs.is_error() || self.rhs.is_error() {
+                "error".to_string()
+            } else {
+                format!("(% {} {})", self.lhs.to_string(), self.rhs.to_string())
             }
         }
 
-        toks
-    }
+        fn is_error(&self) -> bool {
+            self.lhs.is_error() || self.rhs.is_error()
+        }
 
-    //TODO: Complete this function
+        fn span(&self) -> Span {
+            self.span
+        }
+
+        fn node(&self) -> Node<'_> {
+            Node::Binary(BinOp::Mod, &*self.lhs, &*self.rhs)
+        }
+
+        fn take_children(&mut self, stack: &mut Vec<std::rc::Rc<dyn Exp>>) {
+            take(&mut self.lhs, stack);
+            take(&mut self.rhs, stack);
+        }
+    }
+
+    #[derive(Clone)]
+    pub struct CmpExp {
+        pub op: CmpOp,
+        pub lhs: std::rc::Rc<dyn Exp>,
+        pub rhs: std::rc::Rc<dyn Exp>,
+        pub span: Span,
+    }
+
+    impl Exp for CmpExp {
+        fn print(&self) {
+            print!("({} ", self.op.symbol());
+            self.lhs.print();
+            print!(" ");
+            self.rhs.print();
+            print!(")");
+        }
+
+        fn to_string(&self) -> String {
+            if self.lhs.is_error() || self.rhs.is_error() {
+                "error".to_string()
+            } else {
+                format!(
+                    "({} {} {})",
+                    self.op.symbol(),
+                    self.lhs.to_string(),
+                    self.rhs.to_string()
+                )
+            }
+        }
+
+        fn is_error(&self) -> bool {
+            self.lhs.is_error() || self.rhs.is_error()
+        }
+
+        fn span(&self) -> Span {
+            self.span
+        }
+
+        fn node(&self) -> Node<'_> {
+            Node::Compare(self.op, &*self.lhs, &*self.rhs)
+        }
+
+        fn take_children(&mut self, stack: &mut Vec<std::rc::Rc<dyn Exp>>) {
+            take(&mut self.lhs, stack);
+            take(&mut self.rhs, stack);
+        }
+    }
+
+    #[derive(Clone)]
+    pub struct AndExp {
+        pub lhs: std::rc::Rc<dyn Exp>,
+        pub rhs: std::rc::Rc<dyn Exp>,
+        pub span: Span,
+    }
+
+    impl Exp for AndExp {
+        fn print(&self) {
+            print!("(and ");
+            self.lhs.print();
+            print!(" ");
+            self.rhs.print();
+            print!(")");
+        }
+
+        fn to_string(&self) -> String {
+            if self.lhs.is_error() || self.rhs.is_error() {
+                "error".to_string()
+            } else {
+                format!("(and {} {})", self.lhs.to_string(), self.rhs.to_string())
+            }
+        }
+
+        fn is_error(&self) -> bool {
+            self.lhs.is_error() || self.rhs.is_error()
+        }
+
+        fn span(&self) -> Span {
+            self.span
+        }
+
+        fn node(&self) -> Node<'_> {
+            Node::And(&*self.lhs, &*self.rhs)
+        }
+
+        fn take_children(&mut self, stack: &mut Vec<std::rc::Rc<dyn Exp>>) {
+            take(&mut self.lhs, stack);
+            take(&mut self.rhs, stack);
+        }
+    }
+
+    #[derive(Clone)]
+    pub struct OrExp {
+        pub lhs: std::rc::Rc<dyn Exp>,
+        pub rhs: std::rc::Rc<dyn Exp>,
+        pub span: Span,
+    }
+
+    impl Exp for OrExp {
+        fn print(&self) {
+            print!("(or ");
+            self.lhs.print();
+            print!(" ");
+            self.rhs.print();
+            print!(")");
+        }
+
+        fn to_string(&self) -> String {
+            if self.lhs.is_error() || self.rhs.is_error() {
+                "error".to_string()
+            } else {
+                format!("(or {} {})", self.lhs.to_string(), self.rhs.to_string())
+            }
+        }
+
+        fn is_error(&self) -> bool {
+            self.lhs.is_error() || self.rhs.is_error()
+        }
+
+        fn span(&self) -> Span {
+            self.span
+        }
+
+        fn node(&self) -> Node<'_> {
+            Node::Or(&*self.lhs, &*self.rhs)
+        }
+
+        fn take_children(&mut self, stack: &mut Vec<std::rc::Rc<dyn Exp>>) {
+            take(&mut self.lhs, stack);
+            take(&mut self.rhs, stack);
+        }
+    }
+
+    #[derive(Clone)]
+    pub struct NotExp {
+        pub arg: std::rc::Rc<dyn Exp>,
+        pub span: Span,
+    }
+
+    impl Exp for NotExp {
+        fn print(&self) {
+            print!("(not ");
+            self.arg.print();
+            print!(")");
+        }
+
+        fn to_string(&self) -> String {
+            if self.arg.is_error() {
+                "error".to_string()
+            } else {
+                format!("(not {})", self.arg.to_string())
+            }
+        }
+
+        fn is_error(&self) -> bool {
+            self.arg.is_error()
+        }
+
+        fn span(&self) -> Span {
+            self.span
+        }
+
+        fn node(&self) -> Node<'_> {
+            Node::Not(&*self.arg)
+        }
+
+        fn take_children(&mut self, stack: &mut Vec<std::rc::Rc<dyn Exp>>) {
+            take(&mut self.arg, stack);
+        }
+    }
+
+    #[derive(Clone)]
+    pub struct IfExp {
+        pub cond: std::rc::Rc<dyn Exp>,
+        pub then: std::rc::Rc<dyn Exp>,
+        pub els: std::rc::Rc<dyn Exp>,
+        pub span: Span,
+    }
+
+    impl Exp for IfExp {
+        fn print(&self) {
+            print!("(if ");
+            self.cond.print();
+            print!(" ");
+            self.then.print();
+            print!(" ");
+            self.els.print();
+            print!(")");
+        }
+
+        fn to_string(&self) -> String {
+            if self.is_error() {
+                "error".to_string()
+            } else {
+                format!(
+                    "(if {} {} {})",
+                    self.cond.to_string(),
+                    self.then.to_string(),
+                    self.els.to_string()
+                )
+            }
+        }
+
+        fn is_error(&self) -> bool {
+            self.cond.is_error() || self.then.is_error() || self.els.is_error()
+        }
+
+        fn span(&self) -> Span {
+            self.span
+        }
+
+        fn node(&self) -> Node<'_> {
+            Node::If(&*self.cond, &*self.then, &*self.els)
+        }
+
+        fn take_children(&mut self, stack: &mut Vec<std::rc::Rc<dyn Exp>>) {
+            take(&mut self.cond, stack);
+            take(&mut self.then, stack);
+            take(&mut self.els, stack);
+        }
+    }
+
+    // One (name value) pair in a let, span is where the name was written
+    #[derive(Clone)]
+    pub struct LetBinding {
+        pub name: String,
+        pub value: std::rc::Rc<dyn Exp>,
+        pub span: Span,
+    }
+
+    #[derive(Clone)]
+    pub struct LetExp {
+        pub sequential: bool, // let* rather than let
+        pub bindings: Vec<LetBinding>,
+        pub body: std::rc::Rc<dyn Exp>,
+        pub span: Span,
+    }
+
+    impl LetExp {
+        fn keyword(&self) -> &'static str {
+            if self.sequential {
+                "let*"
+            } else {
+                "let"
+            }
+        }
+    }
+
+    impl Exp for LetExp {
+        fn print(&self) {
+            print!("({} (", self.keyword());
+            for (i, binding) in self.bindings.iter().enumerate() {
+                if i > 0 {
+                    print!(" ");
+                }
+                print!("({} ", binding.name);
+                binding.value.print();
+                print!(")");
+            }
+            print!(") ");
+            self.body.print();
+            print!(")");
+        }
+
+        fn to_string(&self) -> String {
+            if self.is_error() {
+                return "error".to_string();
+            }
+            let bindings: Vec<String> = self
+                .bindings
+                .iter()
+                .map(|b| format!("({} {})", b.name, b.value.to_string()))
+                .collect();
+            format!(
+                "({} ({}) {})",
+                self.keyword(),
+                bindings.join(" "),
+                self.body.to_string()
+            )
+        }
+
+        fn is_error(&self) -> bool {
+            self.bindings.iter().any(|b| b.value.is_error()) || self.body.is_error()
+        }
+
+        fn span(&self) -> Span {
+            self.span
+        }
+
+        fn node(&self) -> Node<'_> {
+            let kind = if self.sequential {
+                LetKind::Sequential
+            } else {
+                LetKind::Parallel
+            };
+            Node::Let(kind, &self.bindings, &*self.body)
+        }
+
+        fn take_children(&mut self, stack: &mut Vec<std::rc::Rc<dyn Exp>>) {
+            for binding in &mut self.bindings {
+                take(&mut binding.value, stack);
+            }
+            take(&mut self.body, stack);
+        }
+    }
+
+    // A lambda parameter, span is where its name was written
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct Param {
+        pub name: String,
+        pub span: Span,
+    }
+
+    #[derive(Clone)]
+    pub struct LambdaExp {
+        pub params: Vec<Param>,
+        pub body: std::rc::Rc<dyn Exp>,
+        pub span: Span,
+    }
+
+    impl Exp for LambdaExp {
+        fn print(&self) {
+            print!("{}", self.to_string());
+        }
+
+        fn to_string(&self) -> String {
+            if self.body.is_error() {
+                return "error".to_string();
+            }
+            let params: Vec<&str> = self.params.iter().map(|p| p.name.as_str()).collect();
+            format!("(lambda ({}) {})", params.join(" "), self.body.to_string())
+        }
+
+        fn is_error(&self) -> bool {
+            self.body.is_error()
+        }
+
+        fn span(&self) -> Span {
+            self.span
+        }
+
+        fn node(&self) -> Node<'_> {
+            Node::Lambda(&self.params, &self.body)
+        }
+
+        fn take_children(&mut self, stack: &mut Vec<std::rc::Rc<dyn Exp>>) {
+            take(&mut self.body, stack);
+        }
+    }
+
+    #[derive(Clone)]
+    pub struct CallExp {
+        pub func: std::rc::Rc<dyn Exp>,
+        pub args: Vec<std::rc::Rc<dyn Exp>>,
+        pub span: Span,
+    }
+
+    impl Exp for CallExp {
+        fn print(&self) {
+            print!("(");
+            self.func.print();
+            for arg in &self.args {
+                print!(" ");
+                arg.print();
+            }
+            print!(")");
+        }
+
+        fn to_string(&self) -> String {
+            if self.is_error() {
+                return "error".to_string();
+            }
+            let mut parts = vec![self.func.to_string()];
+            parts.extend(self.args.iter().map(|arg| arg.to_string()));
+            format!("({})", parts.join(" "))
+        }
+
+        fn is_error(&self) -> bool {
+            self.func.is_error() || self.args.iter().any(|arg| arg.is_error())
+        }
+
+        fn span(&self) -> Span {
+            self.span
+        }
+
+        fn node(&self) -> Node<'_> {
+            Node::Call(&*self.func, &self.args)
+        }
+
+        fn take_children(&mut self, stack: &mut Vec<std::rc::Rc<dyn Exp>>) {
+            take(&mut self.func, stack);
+            for arg in &mut self.args {
+                take(arg, stack);
+            }
+        }
+    }
+
+    #[derive(Clone)]
+    pub struct LitExp {
+        pub n: BigInt, // Literals can be any length, evaluating checks that they fit
+        pub span: Span,
+    }
+
+    impl Exp for LitExp {
+        fn print(&self) {
+            print!("{}", self.to_string());
+        }
+
+        fn to_string(&self) -> String {
+            Literal::Int(&self.n).to_source()
+        }
+
+        fn is_error(&self) -> bool {
+            false
+        }
+
+        fn span(&self) -> Span {
+            self.span
+        }
+
+        fn node(&self) -> Node<'_> {
+            Node::Lit(Literal::Int(&self.n))
+        }
+    }
+
+    #[derive(Clone)]
+    pub struct VarExp {
+        pub name: String,
+        pub span: Span,
+    }
+
+    impl Exp for VarExp {
+        fn print(&self) {
+            print!("{}", self.name);
+        }
+
+        fn to_string(&self) -> String {
+            self.name.clone()
+        }
+
+        fn is_error(&self) -> bool {
+            false
+        }
+
+        fn span(&self) -> Span {
+            self.span
+        }
+
+        fn node(&self) -> Node<'_> {
+            Node::Var(&self.name)
+        }
+    }
+
+    // A name the parser found in its Builtins, rather than one bound by a let, lambda or define
+    #[derive(Clone)]
+    pub struct BuiltinExp {
+        pub builtin: std::rc::Rc<Builtin>,
+        pub span: Span,
+    }
+
+    impl Exp for BuiltinExp {
+        fn print(&self) {
+            print!("{}", self.builtin.name);
+        }
+
+        fn to_string(&self) -> String {
+            self.builtin.name.clone()
+        }
+
+        fn is_error(&self) -> bool {
+            false
+        }
+
+        fn span(&self) -> Span {
+            self.span
+        }
+
+        fn node(&self) -> Node<'_> {
+            Node::Builtin(&self.builtin)
+        }
+    }
+
+    #[derive(Clone)]
+    pub struct FloatExp {
+        pub x: f64, // Written with a fraction or exponent, 1.5 or 2e10
+        pub span: Span,
+    }
+
+    impl Exp for FloatExp {
+        fn print(&self) {
+            print!("{}", self.to_string());
+        }
+
+        fn to_string(&self) -> String {
+            Literal::Float(self.x).to_source()
+        }
+
+        fn is_error(&self) -> bool {
+            false
+        }
+
+        fn span(&self) -> Span {
+            self.span
+        }
+
+        fn node(&self) -> Node<'_> {
+            Node::Lit(Literal::Float(self.x))
+        }
+    }
+
+    // ---------------------------------------------------------------------------------------------------------------------
+
+    // Words that start a paren form, which can't be used as variable names
+    pub const KEYWORDS: &[&str] = &["and", "or", "not", "if", "let", "let*", "lambda", "define"];
+
+    // The regexes are compiled the first time they are used rather than on every call to lex or parse
+    // Digits are ASCII only, \d would also match digits such as ٣ that don't parse as a number
+    static INT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[0-9]+$").unwrap());
+    static FLOAT: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^[0-9]+(\.[0-9]+)?([eE][+-]?[0-9]+)?$").unwrap());
+    static NAME: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").unwrap());
+    // The number at the start of the rest of the input, 12, 1.5, 2e10, 1.5e-3
+    static NUMBER: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^[0-9]+(\.[0-9]+)?([eE][+-]?[0-9]+)?").unwrap());
+
+    // Reads the tokens that stand for themselves, numbers and names
+    pub(crate) struct Atoms;
+
+    impl Atoms {
+        // Letters, digits and _, not starting with a digit
+        pub(crate) fn is_name(text: &str) -> bool {
+            NAME.is_match(text)
+        }
+
+        // The literal or variable tok is, or why it can't be one
+        pub(crate) fn parse(tok: Token) -> Result<std::rc::Rc<dyn Exp>, ParseError> {
+            Atoms::node(Atoms::classify(tok)?, tok.span)
+        }
+
+        // The node for an atom parsed from span
+        fn node(atom: Atom, span: Span) -> Result<std::rc::Rc<dyn Exp>, ParseError> {
+            Ok(match atom {
+                Atom::Int(digits) => std::rc::Rc::new(LitExp {
+                    n: Atoms::int(digits, span)?,
+                    span,
+                }),
+                Atom::Float(x) => std::rc::Rc::new(FloatExp { x, span }),
+                Atom::Name(name) => std::rc::Rc::new(VarExp {
+                    name: name.to_string(),
+                    span,
+                }),
+            })
+        }
+
+        // The integer the digits of an Atom::Int spell, parsed from span
+        pub(crate) fn int(digits: &str, span: Span) -> Result<BigInt, ParseError> {
+            digits.parse().map_err(|_| ParseError::InvalidCharacter {
+                found: digits.to_string(),
+                span,
+            })
+        }
+
+        // What kind of atom tok is, without building a node for it
+        pub(crate) fn classify(tok: Token<'_>) -> Result<Atom<'_>, ParseError> {
+            if INT.is_match(tok.text) {
+                Ok(Atom::Int(tok.text))
+            } else if FLOAT.is_match(tok.text) {
+                match tok.text.parse::<f64>() {
+                    Ok(x) if x.is_finite() => Ok(Atom::Float(x)),
+                    _ => Err(ParseError::LiteralOutOfRange {
+                        literal: tok.text.to_string(),
+                        span: tok.span,
+                    }),
+                }
+            } else if KEYWORDS.contains(&tok.text) {
+                Err(ParseError::ReservedWord {
+                    word: tok.text.to_string(),
+                    span: tok.span,
+                })
+            } else if NAME.is_match(tok.text) {
+                Ok(Atom::Name(tok.text))
+            } else {
+                Err(ParseError::InvalidCharacter {
+                    found: tok.text.to_string(),
+                    span: tok.span,
+                })
+            }
+        }
+    }
+
+    pub(crate) enum Atom<'a> {
+        Int(&'a str), // The digits, to be parsed as whatever size of integer is wanted
+        Float(f64),
+        Name(&'a str),
+    }
+
+    pub fn lex(exp: &str) -> Vec<Token<'_>> {
+        /*
+
+        */
+        let mut toks: Vec<Token> = Vec::new(); // We initialize an empty vector to store tokens
+
+        // Where the last token started, moved forward to each new one so lines and columns are counted once
+        let mut at = Position {
+            offset: 0,
+            line: 1,
+            col: 1,
+        };
+
+        let mut i = 0;
+        while let Some(c) = exp[i..].chars().next() {
+            // We iterate over characters, working out where the token starting at each one ends
+            let end = match c {
+                '+' | '-' | '*' | '/' | '%' | '^' | '=' | '(' | ')' => i + 1,
+                // <= and >= are a single token
+                '<' | '>' if exp[i + 1..].starts_with('=') => i + 2,
+                '<' | '>' => i + 1,
+                // A number takes all of its digits, along with a fraction and exponent if it has them
+                // The - in 1.5e-3 is part of the number rather than an operator
+                // Letters, digits, _ and . straight after it stay in the token, so 2x is one invalid token rather than
+                // 2 followed by the name x
+                '0'..='9' => {
+                    let end = i + NUMBER.find(&exp[i..]).unwrap().end();
+                    exp[end..]
+                        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+                        .map_or(exp.len(), |n| end + n)
+                }
+                _ if c.is_whitespace() => {
+                    i += c.len_utf8();
+                    continue;
+                }
+                // Anything else runs until the next space, paren or operator, which makes a name, a keyword,
+                // or something the parser rejects
+                _ => {
+                    let end = exp[i..]
+                        .find(|c: char| c.is_whitespace() || "+-*/%^=<>()".contains(c))
+                        .map_or(exp.len(), |n| i + n);
+                    // The * in let* is part of the keyword rather than an operator
+                    if &exp[i..end] == "let" && exp[end..].starts_with('*') {
+                        end + 1
+                    } else {
+                        end
+                    }
+                }
+            };
+            toks.push(token(exp, &mut at, &exp[i..end]));
+            i = end;
+        }
+
+        toks
+    }
+
+    // A byte offset into the input along with the line and column it is on
+    struct Position {
+        offset: usize,
+        line: usize,
+        col: usize,
+    }
+
+    // Builds the token for text, which must be a slice of exp, working out its span from where the slice sits in exp
+    // at must be at or before text, and is moved up to it
+    fn token<'a>(exp: &str, at: &mut Position, text: &'a str) -> Token<'a> {
+        let start = text.as_ptr() as usize - exp.as_ptr() as usize;
+        for c in exp[at.offset..start].chars() {
+            if c == '\n' {
+                at.line += 1;
+                at.col = 1;
+            } else {
+                at.col += 1;
+            }
+        }
+        at.offset = start;
+        Token {
+            text,
+            span: Span {
+                start,
+                end: start + text.len(),
+                line: at.line,
+                col: at.col,
+            },
+        }
+    }
+
+    //TODO: Complete this function
     //     /*
     //         expect -> given a mutable vector with chars within, check if the token you are looking for is the one present at the top of the toks vector.
 
@@ -312,11 +1798,12 @@ pub mod parser {
     //         -> expect(x, "b") then return a panic! (since "a" is at the front of the vector here)
 
     //     */
-    pub fn expect<'a>(toks: &mut Vec<&'a str>, tok: &'a str) {
-        if toks[0] == tok {
-            toks.remove(0);
+    // Removing the first token shifts every other one down, so the parser takes tokens with Tokens::expect instead
+    pub fn expect<'a>(toks: &mut Vec<Token<'a>>, tok: &str) -> Token<'a> {
+        if toks[0].text == tok {
+            toks.remove(0)
         } else {
-            panic!("EXPECTED {} BUT GOT {}", tok, toks[0])
+            panic!("EXPECTED {} BUT GOT {}", tok, toks[0].text)
         }
 
         // let first_token_option = toks.first();
@@ -340,11 +1827,11 @@ pub mod parser {
     //         -> peek(x, 1) would return "b"
     //         -> peek(x, 5) would return ""
     //     */
-    pub fn peek<'a>(toks: &'a Vec<&str>, n: usize) -> &'a str {
+    pub fn peek<'a>(toks: &[Token<'a>], n: usize) -> &'a str {
         if toks.len() > n {
-            return &toks[n];
+            toks[n].text
         } else {
-            &""
+            ""
         }
         // match toks.get(n) {
         //     Some(token) => token,
@@ -352,7 +1839,66 @@ pub mod parser {
         // }
     }
 
-    pub fn parse(ts: Vec<&str>) -> std::rc::Rc<dyn Exp> {
+    // The tokens left to parse, a view of the lexed tokens that taking one just moves past, so parsing is linear
+    // It derefs to the remaining tokens, so peek(toks, 0) and toks.first() see the next one
+    struct Tokens<'t, 'a> {
+        rest: &'t [Token<'a>],
+    }
+
+    impl<'t, 'a> Tokens<'t, 'a> {
+        fn new(toks: &'t [Token<'a>]) -> Tokens<'t, 'a> {
+            Tokens { rest: toks }
+        }
+
+        // Takes the next token, which the caller has already checked is tok
+        fn expect(&mut self, tok: &str) -> Token<'a> {
+            match self.rest.split_first() {
+                Some((first, rest)) if first.text == tok => {
+                    self.rest = rest;
+                    *first
+                }
+                _ => panic!("EXPECTED {} BUT GOT {}", tok, peek(self.rest, 0)),
+            }
+        }
+    }
+
+    impl<'a> std::ops::Deref for Tokens<'_, 'a> {
+        type Target = [Token<'a>];
+
+        fn deref(&self) -> &[Token<'a>] {
+            self.rest
+        }
+    }
+
+    pub fn parse(ts: Vec<Token>) -> std::rc::Rc<dyn Exp> {
+        // Every parse failure collapses into an ErrorExp here, use try_parse to find out what went wrong
+        match try_parse(ts) {
+            Ok(ast) => ast,
+            Err(_) => std::rc::Rc::new(ErrorExp),
+        }
+    }
+
+    pub fn try_parse(ts: Vec<Token>) -> Result<std::rc::Rc<dyn Exp>, ParseError> {
+        try_parse_with_options(ts, &ParserOptions::default())
+    }
+
+    // Parses like try_parse, resolving function names against builtins rather than the default set
+    pub fn try_parse_with(
+        ts: Vec<Token>,
+        builtins: &Builtins,
+    ) -> Result<std::rc::Rc<dyn Exp>, ParseError> {
+        let options = ParserOptions {
+            builtins: builtins.clone(),
+            ..ParserOptions::default()
+        };
+        try_parse_with_options(ts, &options)
+    }
+
+    // Parses like try_parse, with the operators and builtins in options
+    pub fn try_parse_with_options(
+        ts: Vec<Token>,
+        options: &ParserOptions,
+    ) -> Result<std::rc::Rc<dyn Exp>, ParseError> {
         //TODO: Complete this function
         /*
             The lex function is responsible for breaking down the input expression into tokens.
@@ -360,199 +1906,738 @@ pub mod parser {
             The parse function is where you convert the tokenized input into an abstract syntax tree (AST).
             Think about how you can recursively build the tree by combining expressions based on the tokens
         */
-        let mut toks = ts;
-
-        pub fn parse_exp(toks: &mut Vec<&str>) -> std::rc::Rc<dyn Exp> {
-            /*
-                This function should recursively parse an expression based on the tokens
-                Consider how each type of expression (PlusExp, MinusExp, etc.) should be parsed differently
-
-            */
-
-            // Consider the following example to parse (+ 1 2)
-            let num = Regex::new(r"^\d+$").unwrap(); // Digits
-            let ops = Regex::new(r"^(\+|-|\*|\^)$").unwrap(); // Operators
-
-            let nexttok = toks[0];
-            // + + 1 2 3
-            // + 1 2 3
-            match nexttok {
-                "+" => {
-                    expect(toks, nexttok); // This should remove the "+" from the front of toks
-                    let arg1 = parse_exp(toks); // We recursively parse the first arg of "+"
-                    let arg2 = parse_exp(toks); // and the same recursive parse of the second arg of "+""
-                    return std::rc::Rc::new(PlusExp {
-                        lhs: arg1,
-                        rhs: arg2,
-                    });
+        let mut toks = Tokens::new(&ts);
+        let cx = Context::new(&toks, options);
+        let ast = parse_exp(&mut toks, &cx)?;
+        match peek(&toks, 0) {
+            "" => Ok(ast),
+            ")" => Err(ParseError::UnbalancedParen {
+                span: toks[0].span,
+                open: None,
+            }),
+            found => Err(ParseError::TrailingTokens {
+                found: found.to_string(),
+                span: toks[0].span,
+            }),
+        }
+    }
+
+    // A sequence of top-level forms, which unlike a single expression can include defines
+    pub fn parse_program(ts: Vec<Token>) -> Result<Program, ParseError> {
+        parse_program_with_options(ts, &ParserOptions::default())
+    }
+
+    pub fn parse_program_with(ts: Vec<Token>, builtins: &Builtins) -> Result<Program, ParseError> {
+        let options = ParserOptions {
+            builtins: builtins.clone(),
+            ..ParserOptions::default()
+        };
+        parse_program_with_options(ts, &options)
+    }
+
+    pub fn parse_program_with_options(
+        ts: Vec<Token>,
+        options: &ParserOptions,
+    ) -> Result<Program, ParseError> {
+        let mut toks = Tokens::new(&ts);
+        let cx = Context::new(&toks, options);
+        // Defined names are visible to every form, even the ones before the define, so they hide builtins everywhere
+        for (i, tok) in toks.iter().enumerate() {
+            if tok.text == "define" && i > 0 && toks[i - 1].text == "(" {
+                match peek(&toks, i + 1) {
+                    "(" => cx.bind(peek(&toks, i + 2)),
+                    name => cx.bind(name),
                 }
-                "-" => {
-                    expect(toks, nexttok);
-                    let arg1 = parse_exp(toks);
-                    let arg2 = parse_exp(toks);
-                    return std::rc::Rc::new(MinusExp {
-                        lhs: arg1,
-                        rhs: arg2,
-                    });
+            }
+        }
+        let mut forms = vec![];
+        while !peek(&toks, 0).is_empty() {
+            if peek(&toks, 0) == ")" {
+                return Err(ParseError::UnbalancedParen {
+                    span: toks[0].span,
+                    open: None,
+                });
+            }
+            if peek(&toks, 0) == "(" && peek(&toks, 1) == "define" {
+                forms.push(parse_define(&mut toks, &cx)?);
+            } else {
+                forms.push(Form::Exp(parse_exp(&mut toks, &cx)?));
+            }
+        }
+        Ok(Program { forms })
+    }
+
+    // What the parser accepts, and what names and symbols mean to it
+    #[derive(Clone, Debug, Default)]
+    pub struct ParserOptions {
+        pub operators: OperatorTable,
+        pub builtins: Builtins,
+        pub dialect: Dialect,
+    }
+
+    impl ParserOptions {
+        // Exactly the grammar in the comment at the top of this file, where (+ 3) => 3 rather than (+ 0 3)
+        pub fn strict_spec() -> ParserOptions {
+            let mut operators = OperatorTable::default();
+            operators.register(Operator::new(
+                "+",
+                |lhs, rhs, span| std::rc::Rc::new(PlusExp { lhs, rhs, span }),
+                Assoc::Left,
+                1,
+                Unary::Identity,
+            ));
+            ParserOptions {
+                operators,
+                ..ParserOptions::default()
+            }
+        }
+    }
+
+    // Which forms the parser accepts, on top of what the OperatorTable declares
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct Dialect {
+        pub unary: UnaryStyle,
+        pub bare_binary: bool, // + 1 2, an operator without parens
+        pub kary: bool,        // (+ 1 2 3), an operator with more than 2 operands
+        // How deeply paren forms and operators can nest, or None for no limit
+        // The parser recurses once per level, so without a limit a deep enough input overflows the stack
+        pub max_depth: Option<usize>,
+    }
+
+    impl Default for Dialect {
+        fn default() -> Dialect {
+            Dialect {
+                unary: UnaryStyle::Declared,
+                bare_binary: true,
+                kary: true,
+                // Deeper than formulas written by hand, and shallow enough for a debug build to parse nested lets on
+                // the 2MB stack Rust gives spawned threads
+                max_depth: Some(128),
+            }
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub enum UnaryStyle {
+        Declared,   // Each operator's unary form is whatever its entry in the OperatorTable says
+        Disallowed, // Every operator needs at least 2 operands, (- 2) is an error
+    }
+
+    // What the parse functions share while parsing one input
+    struct Context<'b> {
+        eof: Span, // Where errors at the end of the input point
+        options: &'b ParserOptions,
+        bound: std::cell::RefCell<Vec<String>>, // The names bound around the expression being parsed, innermost last
+        depth: std::cell::Cell<usize>, // How many paren forms and operators the expression being parsed is inside
+    }
+
+    impl<'b> Context<'b> {
+        fn new(toks: &[Token], options: &'b ParserOptions) -> Context<'b> {
+            Context {
+                eof: eof_span(toks),
+                options,
+                bound: Default::default(),
+                depth: Default::default(),
+            }
+        }
+
+        fn bind(&self, name: &str) {
+            self.bound.borrow_mut().push(name.to_string());
+        }
+
+        // How many names are bound, so they can be unbound again once their scope ends
+        fn bound_len(&self) -> usize {
+            self.bound.borrow().len()
+        }
+
+        fn unbind_to(&self, len: usize) {
+            self.bound.borrow_mut().truncate(len);
+        }
+
+        // The builtin that name refers to, unless a let, lambda or define has bound it
+        fn builtin(&self, name: &str) -> Option<&'b std::rc::Rc<Builtin>> {
+            if self.bound.borrow().iter().any(|bound| bound == name) {
+                None
+            } else {
+                self.options.builtins.get(name)
+            }
+        }
+    }
+
+    // Errors at the end of the input point just past the last token
+    pub(crate) fn eof_span(toks: &[Token]) -> Span {
+        match toks.last() {
+            Some(last) => Span {
+                start: last.span.end,
+                col: last.span.col + last.text.chars().count(),
+                ..last.span
+            },
+            None => Span {
+                line: 1,
+                col: 1,
+                ..Span::default()
+            },
+        }
+    }
+
+    // The span of the next token, or of the end of the input if there are no tokens left
+    fn here(toks: &[Token], eof: Span) -> Span {
+        match toks.first() {
+            Some(tok) => tok.span,
+            None => eof,
+        }
+    }
+
+    fn parse_exp(toks: &mut Tokens, cx: &Context) -> Result<std::rc::Rc<dyn Exp>, ParseError> {
+        let nested = peek(toks, 0) == "(" || cx.options.operators.get(peek(toks, 0)).is_some();
+        if !nested {
+            return parse_form(toks, cx);
+        }
+        // Only paren forms and operators nest, so they are what max_depth limits
+        let depth = cx.depth.get() + 1;
+        if let Some(max) = cx.options.dialect.max_depth {
+            if depth > max {
+                return Err(ParseError::TooDeep {
+                    max,
+                    span: toks[0].span,
+                });
+            }
+        }
+        cx.depth.set(depth);
+        let ast = parse_form(toks, cx);
+        cx.depth.set(depth - 1);
+        ast
+    }
+
+    // An expression, along with everything nested in it
+    fn parse_form(toks: &mut Tokens, cx: &Context) -> Result<std::rc::Rc<dyn Exp>, ParseError> {
+        /*
+            This function should recursively parse an expression based on the tokens
+            Consider how each type of expression (PlusExp, MinusExp, etc.) should be parsed differently
+
+        */
+
+        // Consider the following example to parse (+ 1 2)
+        let nexttok = peek(toks, 0);
+        // + + 1 2 3
+        // + 1 2 3
+        if let Some(operator) = cx.options.operators.get(nexttok) {
+            // Without parens an operator is always binary
+            if !cx.options.dialect.bare_binary {
+                return Err(ParseError::BareOperator {
+                    op: nexttok.to_string(),
+                    span: toks[0].span,
+                });
+            }
+            let start = toks.expect(nexttok).span; // This should remove the "+" from the front of toks
+            let arg1 = parse_exp(toks, cx)?; // We recursively parse the first arg of "+"
+            let arg2 = parse_exp(toks, cx)?; // and the same recursive parse of the second arg of "+""
+            let span = start.to(arg2.span());
+            return Ok((operator.build)(arg1, arg2, span));
+        }
+        match nexttok {
+            "" => Err(ParseError::UnexpectedEof { span: cx.eof }), // We ran out of tokens while still expecting an expression
+            // + 1 (2) wrong
+            // + 1 (-2) fine
+            // + 1 (+1) fine
+
+            // + 1 ( + 1 (+ 1 2))
+            "(" => {
+                let open = toks.expect(nexttok).span;
+                let op = peek(toks, 0);
+                let op_span = here(toks, cx.eof);
+                let operator = cx.options.operators.get(op);
+                if operator.is_none()
+                    && (op == "(" || (Atoms::is_name(op) && !KEYWORDS.contains(&op)))
+                {
+                    // (f 1 2) or ((lambda (x) x) 1), a function call rather than an operator
+                    return parse_call(toks, cx, open);
                 }
-                "*" => {
-                    expect(toks, nexttok);
-                    let arg1 = parse_exp(toks);
-                    let arg2 = parse_exp(toks);
-                    return std::rc::Rc::new(MultExp {
-                        lhs: arg1,
-                        rhs: arg2,
+                let comparison = matches!(op, "=" | "<" | "<=" | ">" | ">=");
+                if operator.is_some() || comparison || KEYWORDS.contains(&op) {
+                    // The item right after a paren should be an operator
+                    toks.expect(op);
+                } else if op.is_empty() {
+                    // The paren is the last thing in the input
+                    return Err(ParseError::UnbalancedParen {
+                        span: cx.eof,
+                        open: Some(open),
                     });
-                }
-                // ^ 2 3 4
-                "^" => {
-                    expect(toks, nexttok);
-                    let arg1 = parse_exp(toks);
-                    let arg2 = parse_exp(toks);
-                    return std::rc::Rc::new(PowExp {
-                        lhs: arg1,
-                        rhs: arg2,
+                } else if op == ")" {
+                    return Err(ParseError::EmptyParens {
+                        span: open.to(op_span),
+                    });
+                } else {
+                    // Return an error if not
+                    return Err(ParseError::OperatorExpected {
+                        found: op.to_string(),
+                        span: op_span,
                     });
                 }
-
-                // + 1 (2) wrong
-                // + 1 (-2) fine
-                // + 1 (+1) fine
-
-                // + 1 ( + 1 (+ 1 2))
-                "(" => {
-                    expect(toks, nexttok);
-                    let op = toks[0];
-                    if ops.is_match(op) {
-                        // The item right after a paren should be an operator
-                        expect(toks, op);
-                    } else {
-                        return std::rc::Rc::new(ErrorExp); // Return an error if not
-                    }
-                    let mut next = peek(toks, 0); // This will not remove the item at the front of toks
-                    let mut args: Vec<std::rc::Rc<dyn Exp>> = vec![]; // A vector to hold args within the parens
-                    while next != ")" {
-                        // Add the args until we see a right hand paren
-                        let next_arg = parse_exp(toks);
-                        args.push(next_arg);
-                        next = peek(toks, 0);
+                if op == "let" || op == "let*" {
+                    // let has a list of bindings rather than operands
+                    return parse_let(toks, cx, op, open);
+                }
+                if op == "lambda" {
+                    return parse_lambda(toks, cx, open);
+                }
+                if op == "define" {
+                    // parse_program handles the defines at the top level, which is the only place they can be
+                    return Err(ParseError::MisplacedDefine { span: op_span });
+                }
+                let mut next = peek(toks, 0); // This will not remove the item at the front of toks
+                let mut args: Vec<std::rc::Rc<dyn Exp>> = vec![]; // A vector to hold args within the parens
+                while next != ")" {
+                    // Add the args until we see a right hand paren
+                    if next.is_empty() {
+                        // The input ended before the paren was closed
+                        return Err(ParseError::UnbalancedParen {
+                            span: cx.eof,
+                            open: Some(open),
+                        });
                     }
-                    expect(toks, ")");
+                    let next_arg = parse_exp(toks, cx)?;
+                    args.push(next_arg);
+                    next = peek(toks, 0);
+                }
+                let close = toks.expect(")").span;
+                let span = open.to(close); // The whole paren form, which is where the outermost node comes from
 
-                    if args.len() == 0 {
-                        return std::rc::Rc::new(ErrorExp);
-                    }
+                if args.is_empty() {
+                    return Err(ParseError::MissingOperands {
+                        op: op.to_string(),
+                        span,
+                    });
+                }
+                if let Some(operator) = operator {
+                    return operator_form(operator, &args, span, op_span, cx.options.dialect);
+                }
 
-                    match op {
-                        "+" => {
-                            if args.len() == 1 {
-                                // Addition allows for unary addition, thus we can use 0 for the left hand side.
-                                // (+ 1) -> (+ 1 0)
-                                return std::rc::Rc::new(PlusExp {
-                                    lhs: std::rc::Rc::new(LitExp { n: 0 }),
-                                    rhs: std::rc::Rc::clone(&args[0]),
-                                });
-                            }
-                            // For binary or more arguments, we use the arg 0 as our left hand side number and arg 1 as our right hand arg.
-                            // (+ 1 2) -> args[0] = 1 and args[1] = 2
-                            let mut ast = std::rc::Rc::new(PlusExp {
-                                lhs: std::rc::Rc::clone(&args[0]),
-                                rhs: std::rc::Rc::clone(&args[1]),
+                match op {
+                    "=" | "<" | "<=" | ">" | ">=" => {
+                        if args.len() != 2 {
+                            return Err(ParseError::WrongOperandCount {
+                                op: op.to_string(),
+                                expected: 2,
+                                found: args.len(),
+                                span,
                             });
-                            for arg in &args[2..args.len()] {
-                                ast = std::rc::Rc::new(PlusExp {
-                                    lhs: ast, // Since we only allow for binary ASTs, we only allow for binary additions, thus our left hand side would be our previously calculated addition. In this case (+ (+ 1 2) 3) would return the AST you parsed for (+ 1 2)
-                                    rhs: arg.to_owned(),
-                                });
-                            }
-                            return ast;
                         }
-                        "-" => {
-                            if args.len() == 1 {
-                                return std::rc::Rc::new(MinusExp {
-                                    lhs: std::rc::Rc::new(LitExp { n: 0 }),
-                                    rhs: std::rc::Rc::clone(&args[0]),
-                                });
-                            }
-
-                            let mut ast = std::rc::Rc::new(MinusExp {
-                                lhs: std::rc::Rc::clone(&args[0]),
-                                rhs: std::rc::Rc::clone(&args[1]),
+                        let op = match op {
+                            "=" => CmpOp::Eq,
+                            "<" => CmpOp::Lt,
+                            "<=" => CmpOp::Le,
+                            ">" => CmpOp::Gt,
+                            _ => CmpOp::Ge,
+                        };
+                        Ok(std::rc::Rc::new(CmpExp {
+                            op,
+                            lhs: std::rc::Rc::clone(&args[0]),
+                            rhs: std::rc::Rc::clone(&args[1]),
+                            span,
+                        }))
+                    }
+                    "and" | "or" => {
+                        if args.len() == 1 {
+                            return Err(ParseError::UnaryNotAllowed {
+                                op: op.to_string(),
+                                span: op_span,
                             });
-                            for arg in &args[2..args.len()] {
-                                ast = std::rc::Rc::new(MinusExp {
-                                    lhs: ast,
-                                    rhs: arg.to_owned(),
-                                });
-                            }
-                            return ast;
                         }
-                        "*" => {
-                            if args.len() == 1 {
-                                return std::rc::Rc::new(MultExp {
-                                    lhs: std::rc::Rc::new(LitExp { n: 0 }),
-                                    rhs: std::rc::Rc::clone(&args[0]),
-                                });
-                            }
-
-                            let mut ast = std::rc::Rc::new(MultExp {
-                                lhs: std::rc::Rc::clone(&args[0]),
-                                rhs: std::rc::Rc::clone(&args[1]),
+                        if args.len() > 2 && !cx.options.dialect.kary {
+                            return Err(ParseError::WrongOperandCount {
+                                op: op.to_string(),
+                                expected: 2,
+                                found: args.len(),
+                                span,
                             });
-                            for arg in &args[2..args.len()] {
-                                ast = std::rc::Rc::new(MultExp {
-                                    lhs: ast,
-                                    rhs: arg.to_owned(),
-                                });
-                            }
-                            return ast;
                         }
-                      
-                        "^" => {
-                            if args.len() == 1 {
-                                return std::rc::Rc::new(ErrorExp);
+                        // Left associative like +, so (and a b c) -> (and (and a b) c) still stops at the first false
+                        let node = |lhs, rhs, span| -> std::rc::Rc<dyn Exp> {
+                            if op == "and" {
+                                std::rc::Rc::new(AndExp { lhs, rhs, span })
+                            } else {
+                                std::rc::Rc::new(OrExp { lhs, rhs, span })
                             }
-                            let mut ast = std::rc::Rc::new(PowExp {
-                                lhs: std::rc::Rc::clone(&args[args.len() - 2]),
-                                rhs: std::rc::Rc::clone(&args[args.len() - 1]),
+                        };
+                        Ok(operators::fold(&args, Assoc::Left, span, node))
+                    }
+                    "not" => {
+                        if args.len() != 1 {
+                            return Err(ParseError::WrongOperandCount {
+                                op: op.to_string(),
+                                expected: 1,
+                                found: args.len(),
+                                span,
                             });
-                            for arg in &args[..args.len() - 2] {
-                                ast = std::rc::Rc::new(PowExp {
-                                    lhs: std::rc::Rc::clone(arg),
-                                    rhs: ast,
-                                });
-                            }
-
-                            return ast;
                         }
-                        _ => {
-                            expect(toks, nexttok);
-                            return std::rc::Rc::new(LitExp {
-                                n: nexttok.parse().unwrap(),
+                        Ok(std::rc::Rc::new(NotExp {
+                            arg: std::rc::Rc::clone(&args[0]),
+                            span,
+                        }))
+                    }
+                    "if" => {
+                        if args.len() != 3 {
+                            return Err(ParseError::WrongOperandCount {
+                                op: op.to_string(),
+                                expected: 3,
+                                found: args.len(),
+                                span,
                             });
                         }
+                        Ok(std::rc::Rc::new(IfExp {
+                            cond: std::rc::Rc::clone(&args[0]),
+                            then: std::rc::Rc::clone(&args[1]),
+                            els: std::rc::Rc::clone(&args[2]),
+                            span,
+                        }))
                     }
+                    _ => Err(ParseError::OperatorExpected {
+                        found: op.to_string(),
+                        span: op_span,
+                    }),
                 }
+            }
 
-                _ => {
-                    // TODO: complete this match case
-                    // Consider the possibility that you don't match on an op such as "+" above and you don't see an open paren
+            // A right hand paren with no matching left hand paren
+            ")" => Err(ParseError::UnbalancedParen {
+                span: here(toks, cx.eof),
+                open: None,
+            }),
+
+            _ => {
+                // TODO: complete this match case
+                // Consider the possibility that you don't match on an op such as "+" above and you don't see an open paren
+
+                let val = toks[0];
+                let atom = Atoms::classify(val)?;
+                toks.expect(nexttok);
+                if let Atom::Name(name) = atom {
+                    if let Some(builtin) = cx.builtin(name) {
+                        return Ok(std::rc::Rc::new(BuiltinExp {
+                            builtin: std::rc::Rc::clone(builtin),
+                            span: val.span,
+                        }));
+                    }
+                }
+                Atoms::node(atom, val.span)
+            }
+        }
+    }
 
-                    let val = toks[0];
-                    expect(toks, nexttok);
-                    return std::rc::Rc::new(LitExp {
-                        n: val.parse().unwrap(),
+    // An operator applied to the operands in its parens, which op_span is the symbol of
+    fn operator_form(
+        operator: &Operator,
+        args: &[std::rc::Rc<dyn Exp>],
+        span: Span,
+        op_span: Span,
+        dialect: Dialect,
+    ) -> Result<std::rc::Rc<dyn Exp>, ParseError> {
+        let min_arity = match dialect.unary {
+            UnaryStyle::Declared => operator.min_arity,
+            UnaryStyle::Disallowed => operator.min_arity.max(2),
+        };
+        if args.len() > 2 && !dialect.kary {
+            return Err(ParseError::WrongOperandCount {
+                op: operator.symbol.clone(),
+                expected: 2,
+                found: args.len(),
+                span,
+            });
+        }
+        if args.len() < min_arity {
+            if args.len() == 1 {
+                return Err(ParseError::UnaryNotAllowed {
+                    op: operator.symbol.clone(),
+                    span: op_span,
+                });
+            }
+            return Err(ParseError::WrongOperandCount {
+                op: operator.symbol.clone(),
+                expected: min_arity,
+                found: args.len(),
+                span,
+            });
+        }
+        if args.len() > 1 {
+            return Ok(operators::fold(args, operator.assoc, span, operator.build));
+        }
+        match operator.unary {
+            // (+ 3) => 3
+            Unary::Identity => Ok(std::rc::Rc::clone(&args[0])),
+            // (- 2) => (- 0 2), the literal wasn't written, so its span is empty
+            Unary::Lhs(n) => {
+                let lhs = std::rc::Rc::new(LitExp {
+                    n: BigInt::from(n),
+                    span: op_span.before(),
+                });
+                Ok((operator.build)(lhs, std::rc::Rc::clone(&args[0]), span))
+            }
+        }
+    }
+
+    // (let ((x 3) (y (+ x 1))) body), once the ( and the let have been taken
+    fn parse_let(
+        toks: &mut Tokens,
+        cx: &Context,
+        op: &str,
+        open: Span,
+    ) -> Result<std::rc::Rc<dyn Exp>, ParseError> {
+        // An error for the bindings when the next token isn't what was expected
+        let unexpected = |toks: &[Token], open: Span| match toks.first() {
+            None => ParseError::UnbalancedParen {
+                span: cx.eof,
+                open: Some(open),
+            },
+            Some(tok) => ParseError::BadBinding {
+                found: tok.text.to_string(),
+                span: tok.span,
+            },
+        };
+
+        if peek(toks, 0) != "(" {
+            return Err(unexpected(toks, open));
+        }
+        let list_open = toks.expect("(").span;
+        let outer = cx.bound_len();
+        let mut bindings: Vec<LetBinding> = vec![];
+        while peek(toks, 0) != ")" {
+            if peek(toks, 0) != "(" {
+                return Err(unexpected(toks, list_open));
+            }
+            let binding_open = toks.expect("(").span;
+            let tok = take_name(toks, cx, binding_open, |found, span| {
+                ParseError::BadBinding { found, span }
+            })?;
+            if peek(toks, 0) == ")" {
+                // (x) has no value to bind
+                return Err(unexpected(toks, binding_open));
+            }
+            let value = parse_exp(toks, cx)?;
+            if peek(toks, 0) != ")" {
+                return Err(unexpected(toks, binding_open));
+            }
+            toks.expect(")");
+
+            // let binds every name at once, so the same name twice is ambiguous; let* binds them in order
+            if op == "let" {
+                if let Some(first) = bindings.iter().find(|b| b.name == tok.text) {
+                    return Err(ParseError::DuplicateBinding {
+                        form: op.to_string(),
+                        name: tok.text.to_string(),
+                        span: tok.span,
+                        first: first.span,
                     });
                 }
             }
-            std::rc::Rc::new(ErrorExp)
+            if op == "let*" {
+                cx.bind(tok.text);
+            }
+            bindings.push(LetBinding {
+                name: tok.text.to_string(),
+                value,
+                span: tok.span,
+            });
+        }
+        toks.expect(")");
+        if op == "let" {
+            // The values were all parsed in the scope around the let, only the body sees the names
+            for binding in &bindings {
+                cx.bind(&binding.name);
+            }
         }
 
-        let ast = parse_exp(&mut toks);
-        if peek(&toks, 0) == "" {
-            return ast;
-        } else {
-            return std::rc::Rc::new(ErrorExp);
+        let (body, close) = parse_body(toks, cx, op, open)?;
+        cx.unbind_to(outer);
+        Ok(std::rc::Rc::new(LetExp {
+            sequential: op == "let*",
+            bindings,
+            body,
+            span: open.to(close),
+        }))
+    }
+
+    // (lambda (x y) body), once the ( and the lambda have been taken
+    fn parse_lambda(
+        toks: &mut Tokens,
+        cx: &Context,
+        open: Span,
+    ) -> Result<std::rc::Rc<dyn Exp>, ParseError> {
+        match toks.first() {
+            Some(tok) if tok.text == "(" => {}
+            Some(tok) => {
+                return Err(ParseError::BadParameter {
+                    found: tok.text.to_string(),
+                    span: tok.span,
+                })
+            }
+            None => {
+                return Err(ParseError::UnbalancedParen {
+                    span: cx.eof,
+                    open: Some(open),
+                })
+            }
+        }
+        let list_open = toks.expect("(").span;
+        let outer = cx.bound_len();
+        let params = parse_params(toks, cx, list_open)?;
+        let (body, close) = parse_body(toks, cx, "lambda", open)?;
+        cx.unbind_to(outer);
+        Ok(std::rc::Rc::new(LambdaExp {
+            params,
+            body,
+            span: open.to(close),
+        }))
+    }
+
+    // The names in a parameter list up to and including its ), once its ( has been taken
+    // They are bound in cx until the caller unbinds them after the body
+    fn parse_params(
+        toks: &mut Tokens,
+        cx: &Context,
+        list_open: Span,
+    ) -> Result<Vec<Param>, ParseError> {
+        let mut params: Vec<Param> = vec![];
+        while peek(toks, 0) != ")" {
+            let tok = take_name(toks, cx, list_open, |found, span| {
+                ParseError::BadParameter { found, span }
+            })?;
+            if let Some(first) = params.iter().find(|p| p.name == tok.text) {
+                return Err(ParseError::DuplicateBinding {
+                    form: "lambda".to_string(),
+                    name: tok.text.to_string(),
+                    span: tok.span,
+                    first: first.span,
+                });
+            }
+            cx.bind(tok.text);
+            params.push(Param {
+                name: tok.text.to_string(),
+                span: tok.span,
+            });
+        }
+        toks.expect(")");
+        Ok(params)
+    }
+
+    // The single body that ends a let, lambda or define, along with the ) closing the form opened at open
+    fn parse_body(
+        toks: &mut Tokens,
+        cx: &Context,
+        op: &str,
+        open: Span,
+    ) -> Result<(std::rc::Rc<dyn Exp>, Span), ParseError> {
+        let mut body = vec![];
+        while peek(toks, 0) != ")" {
+            if peek(toks, 0).is_empty() {
+                return Err(ParseError::UnbalancedParen {
+                    span: cx.eof,
+                    open: Some(open),
+                });
+            }
+            body.push(parse_exp(toks, cx)?);
+        }
+        let close = toks.expect(")").span;
+        if body.len() != 1 {
+            // Counting whatever came before the body as one operand, the bindings or parameters for instance
+            return Err(ParseError::WrongOperandCount {
+                op: op.to_string(),
+                expected: 2,
+                found: body.len() + 1,
+                span: open.to(close),
+            });
+        }
+        Ok((body.remove(0), close))
+    }
+
+    // Takes the next token if it's a name, bad builds the error for anything else
+    fn take_name<'a>(
+        toks: &mut Tokens<'_, 'a>,
+        cx: &Context,
+        open: Span,
+        bad: impl Fn(String, Span) -> ParseError,
+    ) -> Result<Token<'a>, ParseError> {
+        match toks.first() {
+            Some(tok) if KEYWORDS.contains(&tok.text) => Err(ParseError::ReservedWord {
+                word: tok.text.to_string(),
+                span: tok.span,
+            }),
+            Some(tok) if Atoms::is_name(tok.text) => Ok(toks.expect(tok.text)),
+            Some(tok) => Err(bad(tok.text.to_string(), tok.span)),
+            None => Err(ParseError::UnbalancedParen {
+                span: cx.eof,
+                open: Some(open),
+            }),
+        }
+    }
+
+    // (define x 1) or (define (f x) body), which can only be at the top level of a program
+    fn parse_define(toks: &mut Tokens, cx: &Context) -> Result<Form, ParseError> {
+        let open = toks.expect("(").span;
+        toks.expect("define");
+        let bad_name = |found, span| ParseError::BadDefine { found, span };
+
+        if peek(toks, 0) == "(" {
+            // (define (f x y) body) is (define f (lambda (x y) body))
+            let list_open = toks.expect("(").span;
+            let name = take_name(toks, cx, list_open, bad_name)?;
+            let outer = cx.bound_len();
+            let params = parse_params(toks, cx, list_open)?;
+            let (body, close) = parse_body(toks, cx, "define", open)?;
+            cx.unbind_to(outer);
+            return Ok(Form::Define {
+                name: name.text.to_string(),
+                value: std::rc::Rc::new(LambdaExp {
+                    params,
+                    body,
+                    span: open.to(close),
+                }),
+                span: open.to(close),
+            });
+        }
+
+        let name = take_name(toks, cx, open, bad_name)?;
+        let (value, close) = parse_body(toks, cx, "define", open)?;
+        Ok(Form::Define {
+            name: name.text.to_string(),
+            value,
+            span: open.to(close),
+        })
+    }
+
+    // (f 1 2), once the ( has been taken; the function is any expression, usually a name or a lambda
+    fn parse_call(
+        toks: &mut Tokens,
+        cx: &Context,
+        open: Span,
+    ) -> Result<std::rc::Rc<dyn Exp>, ParseError> {
+        let func = parse_exp(toks, cx)?;
+        let mut args = vec![];
+        while peek(toks, 0) != ")" {
+            if peek(toks, 0).is_empty() {
+                return Err(ParseError::UnbalancedParen {
+                    span: cx.eof,
+                    open: Some(open),
+                });
+            }
+            args.push(parse_exp(toks, cx)?);
+        }
+        let close = toks.expect(")").span;
+        if let Node::Builtin(builtin) = func.node() {
+            // Builtins declare their arity, so calls with the wrong number of arguments are caught here
+            if !builtin.arity.accepts(args.len()) {
+                return Err(ParseError::WrongArgumentCount {
+                    name: builtin.name.clone(),
+                    arity: builtin.arity,
+                    found: args.len(),
+                    span: open.to(close),
+                });
+            }
         }
+        Ok(std::rc::Rc::new(CallExp {
+            func,
+            args,
+            span: open.to(close),
+        }))
     }
 }
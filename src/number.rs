@@ -0,0 +1,479 @@
+// Evaluation over any numeric type: one AST can be evaluated as an i32, i64, i128, BigInt, Rational, f64,
+// or any other type that implements Number
+//   ast.eval_as::<i128>()
+//   ast.eval_as::<Rational>()
+//
+// Division rounds towards negative infinity, and the remainder takes the sign of the divisor, so / and %
+// form a divmod pair with a == b * (/ a b) + (% a b), as in Python
+//   (/ 7 2) => 3, (% 7 2) => 1
+//   (/ (- 7) 2) => -4, (% (- 7) 2) => 1
+//
+// Comparisons, and, or, not and if are evaluated in N too, so conditions can test any N
+//   (if (< 0.5 1) 1 2) => 1 with eval_f64
+// A let is closed, so the names it binds are evaluated as N too
+//   (let ((x 1.5)) (* x 2)) => 3 with eval_f64
+// Functions are only evaluated by eval_value, so a lambda, call or builtin here is a FunctionNotSupported
+
+use crate::parser::{BinOp, CmpOp, EvalError, Exp, LetKind, Literal, Node, Span};
+use crate::{BigInt, Rational};
+use num_integer::Integer;
+use num_traits::ToPrimitive;
+
+// Why an operation on a Number failed, evaluation turns these into an EvalError pointing at the expression
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NumberError {
+    Overflow,           // The result doesn't fit
+    NegativeExponent,   // The type can't represent x ^ -n
+    FractionalExponent, // The type can't represent x ^ 1/2
+    DivisionByZero,
+    OutOfRange,   // A literal that doesn't fit
+    NotAnInteger, // A literal with a fraction, for an integer type
+}
+
+// Clone, since a name bound by a let can be used more than once, and PartialOrd for comparisons
+pub trait Number: Clone + PartialOrd {
+    fn from_literal(lit: Literal<'_>) -> Result<Self, NumberError>;
+    fn add(self, rhs: Self) -> Result<Self, NumberError>;
+    fn sub(self, rhs: Self) -> Result<Self, NumberError>;
+    fn mul(self, rhs: Self) -> Result<Self, NumberError>;
+    fn div(self, rhs: Self) -> Result<Self, NumberError>;
+    fn rem(self, rhs: Self) -> Result<Self, NumberError>;
+    fn pow(self, exponent: Self) -> Result<Self, NumberError>;
+}
+
+// Evaluates exp as an N, pointing any error at the expression it came from
+pub fn eval<N: Number>(exp: &dyn Exp) -> Result<N, EvalError> {
+    eval_node(exp.node(), exp.span())
+}
+
+// Evaluates the expression node, which was parsed from span
+pub fn eval_node<N: Number>(node: Node<'_>, span: Span) -> Result<N, EvalError> {
+    eval_in(node, span, &mut vec![])?.num(span)
+}
+
+// What an expression evaluates to in N, a number or the boolean a condition gives
+#[derive(Clone)]
+enum Val<N> {
+    Num(N),
+    Bool(bool),
+}
+
+impl<N> Val<N> {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Val::Num(_) => "a number",
+            Val::Bool(_) => "a boolean",
+        }
+    }
+
+    // The number, or a TypeMismatch pointing at span, the expression the value came from
+    fn num(self, span: Span) -> Result<N, EvalError> {
+        match self {
+            Val::Num(n) => Ok(n),
+            _ => Err(mismatch("a number", &self, span)),
+        }
+    }
+
+    fn bool(self, span: Span) -> Result<bool, EvalError> {
+        match self {
+            Val::Bool(b) => Ok(b),
+            _ => Err(mismatch("a boolean", &self, span)),
+        }
+    }
+}
+
+fn mismatch<N>(expected: &str, found: &Val<N>, span: Span) -> EvalError {
+    EvalError::TypeMismatch {
+        expected: expected.to_string(),
+        found: found.type_name().to_string(),
+        span,
+    }
+}
+
+// Nested arithmetic keeps its pending operations in a Vec rather than on the stack, as value::eval does
+fn arith<'a, N: Number>(
+    op: BinOp,
+    lhs: &'a dyn Exp,
+    rhs: &'a dyn Exp,
+    span: Span,
+    scope: &mut Scope<'a, N>,
+) -> Result<N, EvalError> {
+    let mut tasks = vec![
+        Arith::Apply(op, rhs.span(), span),
+        Arith::Eval(rhs),
+        Arith::Eval(lhs),
+    ];
+    let mut operands = vec![];
+    while let Some(next) = tasks.pop() {
+        match next {
+            Arith::Eval(exp) => match exp.node() {
+                Node::Binary(op, lhs, rhs) => tasks.extend([
+                    Arith::Apply(op, rhs.span(), exp.span()),
+                    Arith::Eval(rhs),
+                    Arith::Eval(lhs),
+                ]),
+                node => operands.push(eval_in(node, exp.span(), scope)?.num(exp.span())?),
+            },
+            Arith::Apply(op, rhs_span, span) => {
+                let r = operands
+                    .pop()
+                    .expect("an operator comes after both of its operands");
+                let l = operands
+                    .pop()
+                    .expect("an operator comes after both of its operands");
+                operands.push(apply(op, l, r, rhs_span, span)?);
+            }
+        }
+    }
+    Ok(operands.pop().expect("the last operator leaves its result"))
+}
+
+// An operand still to evaluate, or an operator to apply to the last two results
+enum Arith<'a> {
+    Eval(&'a dyn Exp),
+    Apply(BinOp, Span, Span),
+}
+
+// The names bound by the lets around the expression being evaluated, innermost last
+type Scope<'a, N> = Vec<(&'a str, Val<N>)>;
+
+fn eval_in<'a, N: Number>(
+    node: Node<'a>,
+    span: Span,
+    scope: &mut Scope<'a, N>,
+) -> Result<Val<N>, EvalError> {
+    let eval = |exp: &'a dyn Exp, scope: &mut Scope<'a, N>| eval_in(exp.node(), exp.span(), scope);
+    let test = |exp: &'a dyn Exp, scope: &mut Scope<'a, N>| eval(exp, scope)?.bool(exp.span());
+    let value = match node {
+        Node::Lit(lit) => Val::Num(literal(lit, span)?),
+        Node::Binary(op, lhs, rhs) => Val::Num(arith(op, lhs, rhs, span, scope)?),
+        Node::Compare(op, lhs, rhs) => {
+            let l = eval(lhs, scope)?;
+            let r = eval(rhs, scope)?;
+            let result = match (op, l, r) {
+                (CmpOp::Eq, Val::Num(l), Val::Num(r)) => l == r,
+                (CmpOp::Eq, Val::Bool(l), Val::Bool(r)) => l == r,
+                (CmpOp::Eq, l, r) => return Err(mismatch(l.type_name(), &r, rhs.span())),
+                (op, l, r) => {
+                    let (l, r) = (l.num(lhs.span())?, r.num(rhs.span())?);
+                    match op {
+                        CmpOp::Lt => l < r,
+                        CmpOp::Le => l <= r,
+                        CmpOp::Gt => l > r,
+                        _ => l >= r,
+                    }
+                }
+            };
+            Val::Bool(result)
+        }
+        // and and or only evaluate rhs when lhs doesn't already decide the result
+        Node::And(lhs, rhs) => Val::Bool(test(lhs, scope)? && test(rhs, scope)?),
+        Node::Or(lhs, rhs) => Val::Bool(test(lhs, scope)? || test(rhs, scope)?),
+        Node::Not(arg) => Val::Bool(!test(arg, scope)?),
+        // Only the branch that was picked is evaluated
+        Node::If(cond, then, els) => {
+            if test(cond, scope)? {
+                eval(then, scope)?
+            } else {
+                eval(els, scope)?
+            }
+        }
+        Node::Lambda(..) | Node::Call(..) | Node::Builtin(_) => {
+            return Err(EvalError::FunctionNotSupported { span })
+        }
+        Node::Let(kind, bindings, body) => {
+            let outer = scope.len();
+            let mut values = vec![];
+            for binding in bindings {
+                // let evaluates every value in the surrounding scope, let* in the scope built so far
+                let value = eval(&*binding.value, scope)?;
+                match kind {
+                    LetKind::Parallel => values.push((binding.name.as_str(), value)),
+                    LetKind::Sequential => scope.push((binding.name.as_str(), value)),
+                }
+            }
+            scope.extend(values);
+            let result = eval(body, scope);
+            scope.truncate(outer);
+            result?
+        }
+        // Only the names a let binds, eval_with has an Env for any others
+        Node::Var(name) => match scope.iter().rev().find(|(bound, _)| *bound == name) {
+            Some((_, value)) => value.clone(),
+            None => {
+                return Err(EvalError::UnboundVariable {
+                    name: name.to_string(),
+                    span,
+                })
+            }
+        },
+        Node::Error => return Err(EvalError::ErrorNode { span }),
+    };
+    Ok(value)
+}
+
+// The literal as an N, lit was parsed from span
+pub(crate) fn literal<N: Number>(lit: Literal<'_>, span: Span) -> Result<N, EvalError> {
+    N::from_literal(lit).map_err(|err| match err {
+        NumberError::NotAnInteger => EvalError::NotAnInteger {
+            literal: lit.to_string(),
+            span,
+        },
+        _ => EvalError::LiteralOutOfRange {
+            literal: lit.to_string(),
+            span,
+        },
+    })
+}
+
+// l op r, where rhs_span is where r came from and span is the whole expression
+pub(crate) fn apply<N: Number>(
+    op: BinOp,
+    l: N,
+    r: N,
+    rhs_span: Span,
+    span: Span,
+) -> Result<N, EvalError> {
+    let result = match op {
+        BinOp::Plus => l.add(r),
+        BinOp::Minus => l.sub(r),
+        BinOp::Mult => l.mul(r),
+        BinOp::Div => l.div(r),
+        BinOp::Mod => l.rem(r),
+        BinOp::Pow => l.pow(r),
+    };
+    result.map_err(|err| match err {
+        NumberError::NegativeExponent => EvalError::NegativeExponent { span: rhs_span },
+        NumberError::FractionalExponent => EvalError::FractionalExponent { span: rhs_span },
+        NumberError::DivisionByZero => EvalError::DivisionByZero { span },
+        _ => EvalError::Overflow {
+            op: op.symbol().to_string(),
+            span,
+        },
+    })
+}
+
+// The literal as an exact integer, integer types only accept floats that are whole numbers like 2e10
+fn literal_to_big_int(lit: Literal<'_>) -> Result<BigInt, NumberError> {
+    match lit {
+        Literal::Int(n) => Ok(n.clone()),
+        Literal::Float(x) => Rational::from_f64(x)
+            .and_then(|r| r.to_big_int())
+            .ok_or(NumberError::NotAnInteger),
+    }
+}
+
+macro_rules! impl_number_for_int {
+    ($($t:ty),*) => {$(
+        impl Number for $t {
+            fn from_literal(lit: Literal<'_>) -> Result<$t, NumberError> {
+                // Integer literals are converted in place, since cloning the BigInt would allocate on every evaluation
+                let n = match lit {
+                    Literal::Int(n) => <$t>::try_from(n),
+                    _ => <$t>::try_from(&literal_to_big_int(lit)?),
+                };
+                n.map_err(|_| NumberError::OutOfRange)
+            }
+
+            fn add(self, rhs: $t) -> Result<$t, NumberError> {
+                self.checked_add(rhs).ok_or(NumberError::Overflow)
+            }
+
+            fn sub(self, rhs: $t) -> Result<$t, NumberError> {
+                self.checked_sub(rhs).ok_or(NumberError::Overflow)
+            }
+
+            fn mul(self, rhs: $t) -> Result<$t, NumberError> {
+                self.checked_mul(rhs).ok_or(NumberError::Overflow)
+            }
+
+            fn div(self, rhs: $t) -> Result<$t, NumberError> {
+                if rhs == 0 {
+                    return Err(NumberError::DivisionByZero);
+                }
+                let q = self.checked_div(rhs).ok_or(NumberError::Overflow)?;
+                // checked_div truncates, so step down when the exact quotient is negative and not whole
+                if self % rhs != 0 && (self < 0) != (rhs < 0) {
+                    Ok(q - 1)
+                } else {
+                    Ok(q)
+                }
+            }
+
+            fn rem(self, rhs: $t) -> Result<$t, NumberError> {
+                if rhs == 0 {
+                    return Err(NumberError::DivisionByZero);
+                }
+                // MIN % -1 is 0, but checked_rem reports it as an overflow
+                let r = self.checked_rem(rhs).unwrap_or(0);
+                if r != 0 && (r < 0) != (rhs < 0) {
+                    Ok(r + rhs)
+                } else {
+                    Ok(r)
+                }
+            }
+
+            fn pow(self, exponent: $t) -> Result<$t, NumberError> {
+                if exponent < 0 {
+                    return Err(NumberError::NegativeExponent);
+                }
+                match u32::try_from(exponent) {
+                    Ok(exponent) => self.checked_pow(exponent).ok_or(NumberError::Overflow),
+                    // Exponents this large only give a result that fits for these bases
+                    Err(_) => match self {
+                        0 | 1 => Ok(self),
+                        -1 => Ok(if exponent % 2 == 0 { 1 } else { -1 }),
+                        _ => Err(NumberError::Overflow),
+                    },
+                }
+            }
+        }
+    )*};
+}
+
+impl_number_for_int!(i32, i64, i128);
+
+// base ^ exponent for a non-negative exponent, or None if the exponent is too large to compute the result
+fn big_pow(base: &BigInt, exponent: &BigInt) -> Option<BigInt> {
+    match u32::try_from(exponent) {
+        Ok(exponent) => Some(base.pow(exponent)),
+        Err(_) => match i32::try_from(base) {
+            Ok(0) | Ok(1) => Some(base.clone()),
+            Ok(-1) => Some(BigInt::from(if exponent.bit(0) { -1 } else { 1 })),
+            _ => None,
+        },
+    }
+}
+
+impl Number for BigInt {
+    fn from_literal(lit: Literal<'_>) -> Result<BigInt, NumberError> {
+        literal_to_big_int(lit)
+    }
+
+    fn add(self, rhs: BigInt) -> Result<BigInt, NumberError> {
+        Ok(self + rhs)
+    }
+
+    fn sub(self, rhs: BigInt) -> Result<BigInt, NumberError> {
+        Ok(self - rhs)
+    }
+
+    fn mul(self, rhs: BigInt) -> Result<BigInt, NumberError> {
+        Ok(self * rhs)
+    }
+
+    fn div(self, rhs: BigInt) -> Result<BigInt, NumberError> {
+        if rhs == BigInt::from(0) {
+            return Err(NumberError::DivisionByZero);
+        }
+        Ok(self.div_floor(&rhs))
+    }
+
+    fn rem(self, rhs: BigInt) -> Result<BigInt, NumberError> {
+        if rhs == BigInt::from(0) {
+            return Err(NumberError::DivisionByZero);
+        }
+        Ok(self.mod_floor(&rhs))
+    }
+
+    fn pow(self, exponent: BigInt) -> Result<BigInt, NumberError> {
+        if exponent < BigInt::from(0) {
+            return Err(NumberError::NegativeExponent);
+        }
+        big_pow(&self, &exponent).ok_or(NumberError::Overflow)
+    }
+}
+
+impl Number for Rational {
+    fn from_literal(lit: Literal<'_>) -> Result<Rational, NumberError> {
+        match lit {
+            Literal::Int(n) => Ok(Rational::from_integer(n.clone())),
+            Literal::Float(x) => Rational::from_f64(x).ok_or(NumberError::OutOfRange),
+        }
+    }
+
+    fn add(self, rhs: Rational) -> Result<Rational, NumberError> {
+        Ok(self + rhs)
+    }
+
+    fn sub(self, rhs: Rational) -> Result<Rational, NumberError> {
+        Ok(self - rhs)
+    }
+
+    fn mul(self, rhs: Rational) -> Result<Rational, NumberError> {
+        Ok(self * rhs)
+    }
+
+    // Exact, so (/ 7 2) => 7/2
+    fn div(self, rhs: Rational) -> Result<Rational, NumberError> {
+        Ok(self * rhs.recip().ok_or(NumberError::DivisionByZero)?)
+    }
+
+    // a - b * floor(a / b), so (% 7/2 1) => 1/2
+    fn rem(self, rhs: Rational) -> Result<Rational, NumberError> {
+        let quotient = self.clone().div(rhs.clone())?.floor();
+        Ok(self - rhs * Rational::from_integer(quotient))
+    }
+
+    fn pow(self, exponent: Rational) -> Result<Rational, NumberError> {
+        // Only whole exponents keep the result rational, 2 ^ 1/2 for instance isn't
+        let exponent = exponent
+            .to_big_int()
+            .ok_or(NumberError::FractionalExponent)?;
+        let (base, exponent) = if exponent < BigInt::from(0) {
+            // x ^ -n = (1 / x) ^ n
+            (self.recip().ok_or(NumberError::DivisionByZero)?, -exponent)
+        } else {
+            (self, exponent)
+        };
+        let numer = big_pow(base.numer(), &exponent).ok_or(NumberError::Overflow)?;
+        let denom = big_pow(base.denom(), &exponent).ok_or(NumberError::Overflow)?;
+        Ok(Rational::new(numer, denom).expect("a power of a nonzero denominator is nonzero"))
+    }
+}
+
+// IEEE semantics, so overflow gives an infinity and only division by zero fails
+impl Number for f64 {
+    fn from_literal(lit: Literal<'_>) -> Result<f64, NumberError> {
+        match lit {
+            // Literals too large for an f64 become infinite, like any other f64 overflow
+            Literal::Int(n) => Ok(n.to_f64().unwrap_or(f64::INFINITY)),
+            Literal::Float(x) => Ok(x),
+        }
+    }
+
+    fn add(self, rhs: f64) -> Result<f64, NumberError> {
+        Ok(self + rhs)
+    }
+
+    fn sub(self, rhs: f64) -> Result<f64, NumberError> {
+        Ok(self - rhs)
+    }
+
+    fn mul(self, rhs: f64) -> Result<f64, NumberError> {
+        Ok(self * rhs)
+    }
+
+    fn div(self, rhs: f64) -> Result<f64, NumberError> {
+        if rhs == 0.0 {
+            return Err(NumberError::DivisionByZero);
+        }
+        Ok(self / rhs)
+    }
+
+    fn rem(self, rhs: f64) -> Result<f64, NumberError> {
+        if rhs == 0.0 {
+            return Err(NumberError::DivisionByZero);
+        }
+        let r = self % rhs;
+        if r != 0.0 && (r < 0.0) != (rhs < 0.0) {
+            Ok(r + rhs)
+        } else {
+            Ok(r)
+        }
+    }
+
+    fn pow(self, exponent: f64) -> Result<f64, NumberError> {
+        Ok(self.powf(exponent))
+    }
+}
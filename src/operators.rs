@@ -0,0 +1,180 @@
+// The arithmetic operators the parser knows, each declaring how its k-ary and unary forms become binary nodes
+//   (- 3 2 1) => (- (- 3 2) 1), left associative
+//   (^ 2 3 2) => (^ 2 (^ 3 2)), right associative
+//   (- 2)     => (- 0 2), and (* 2) is an error since * needs at least 2 operands
+//   (/ 2) is an error too, since the reciprocal (/ 1 2) would floor to 0 under every integer evaluation
+// Adding an operator is one registration:
+//   let mut options = ParserOptions::default();
+//   options.operators.register(Operator::new("mod", |lhs, rhs, span| Rc::new(ModExp { lhs, rhs, span }), Assoc::Left, 2, Unary::Identity));
+// Symbols other than + - * / % ^ have to be separated from their operands by whitespace, since the lexer reads them
+// as words
+
+use crate::parser::{DivExp, Exp, MinusExp, ModExp, MultExp, PlusExp, PowExp, Span};
+use std::rc::Rc;
+
+// Builds the node for lhs op rhs, which was parsed from span
+pub type Build = fn(Rc<dyn Exp>, Rc<dyn Exp>, Span) -> Rc<dyn Exp>;
+
+// Which way a k-ary form folds
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Assoc {
+    Left,  // (- a b c) => (- (- a b) c)
+    Right, // (^ a b c) => (^ a (^ b c))
+}
+
+// What a form with a single operand means, for operators whose min_arity allows one
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Unary {
+    Identity, // (+ x) => x
+    Lhs(i64), // (- x) => (- 0 x), the operand goes on the right of this literal
+}
+
+#[derive(Clone)]
+pub struct Operator {
+    pub symbol: String,
+    pub build: Build,
+    pub assoc: Assoc,
+    pub min_arity: usize, // 1 allows a single operand, which is then desugared by unary
+    pub unary: Unary,
+}
+
+impl Operator {
+    pub fn new(
+        symbol: impl Into<String>,
+        build: Build,
+        assoc: Assoc,
+        min_arity: usize,
+        unary: Unary,
+    ) -> Operator {
+        Operator {
+            symbol: symbol.into(),
+            build,
+            assoc,
+            min_arity,
+            unary,
+        }
+    }
+}
+
+impl std::fmt::Debug for Operator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Operator")
+            .field("symbol", &self.symbol)
+            .field("assoc", &self.assoc)
+            .field("min_arity", &self.min_arity)
+            .field("unary", &self.unary)
+            .finish_non_exhaustive()
+    }
+}
+
+// OperatorTable::new() is empty, OperatorTable::default() has + - * / % ^
+#[derive(Clone, Debug)]
+pub struct OperatorTable {
+    operators: Vec<Operator>,
+}
+
+impl OperatorTable {
+    pub fn new() -> OperatorTable {
+        OperatorTable { operators: vec![] }
+    }
+
+    // Adds an operator, replacing any registered with the same symbol
+    pub fn register(&mut self, operator: Operator) {
+        self.operators.retain(|op| op.symbol != operator.symbol);
+        self.operators.push(operator);
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&Operator> {
+        self.operators.iter().find(|op| op.symbol == symbol)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Operator> {
+        self.operators.iter()
+    }
+}
+
+impl Default for OperatorTable {
+    fn default() -> OperatorTable {
+        let mut table = OperatorTable::new();
+        table.register(Operator::new(
+            "+",
+            |lhs, rhs, span| Rc::new(PlusExp { lhs, rhs, span }),
+            Assoc::Left,
+            1,
+            Unary::Lhs(0),
+        ));
+        table.register(Operator::new(
+            "-",
+            |lhs, rhs, span| Rc::new(MinusExp { lhs, rhs, span }),
+            Assoc::Left,
+            1,
+            Unary::Lhs(0),
+        ));
+        table.register(Operator::new(
+            "*",
+            |lhs, rhs, span| Rc::new(MultExp { lhs, rhs, span }),
+            Assoc::Left,
+            2,
+            Unary::Identity,
+        ));
+        table.register(Operator::new(
+            "/",
+            |lhs, rhs, span| Rc::new(DivExp { lhs, rhs, span }),
+            Assoc::Left,
+            2,
+            Unary::Identity,
+        ));
+        table.register(Operator::new(
+            "%",
+            |lhs, rhs, span| Rc::new(ModExp { lhs, rhs, span }),
+            Assoc::Left,
+            2,
+            Unary::Identity,
+        ));
+        table.register(Operator::new(
+            "^",
+            |lhs, rhs, span| Rc::new(PowExp { lhs, rhs, span }),
+            Assoc::Right,
+            2,
+            Unary::Identity,
+        ));
+        table
+    }
+}
+
+// Folds two or more operands, parsed from span, into binary nodes
+// Every node but the outermost covers just the operands folded into it
+pub fn fold(
+    args: &[Rc<dyn Exp>],
+    assoc: Assoc,
+    span: Span,
+    build: impl Fn(Rc<dyn Exp>, Rc<dyn Exp>, Span) -> Rc<dyn Exp>,
+) -> Rc<dyn Exp> {
+    let last = args.len() - 1;
+    match assoc {
+        Assoc::Left => {
+            let mut ast = Rc::clone(&args[0]);
+            for (i, arg) in args.iter().enumerate().skip(1) {
+                let span = if i == last {
+                    span
+                } else {
+                    args[0].span().to(arg.span())
+                };
+                ast = build(ast, Rc::clone(arg), span);
+            }
+            ast
+        }
+        Assoc::Right => {
+            let mut ast = Rc::clone(&args[last]);
+            for (i, arg) in args[..last].iter().enumerate().rev() {
+                let span = if i == 0 {
+                    span
+                } else {
+                    arg.span().to(args[last].span())
+                };
+                ast = build(Rc::clone(arg), ast, span);
+            }
+            ast
+        }
+    }
+}
@@ -0,0 +1,325 @@
+// Printing trees too big for one line, breaking and indenting them Lisp-style to fit a line width
+//   Printer::new(24).print(&*ast) =>
+//     (let ((x (+ 1 2))
+//           (y (* 3 4)))
+//       (+ (* x y) (- x y)))
+// A form that fits on the rest of its line is printed as to_string prints it, otherwise its operator and first
+// operand stay on the opening line and the other operands go underneath the first, one per line
+// A first operand that doesn't fit there goes on the next line instead, indented under the opening paren with the
+// rest, so a deeply nested left operand as in (- (- (- 0 1) 2) 3) steps in by indent rather than by its operator
+// let, lambda and define indent their body under the opening paren instead
+// compact packs as many operands onto each of those lines as fit, which suits long runs of small operands
+// resugar prints the forms the parser desugared the way they were probably written, which always parses back to the
+// same tree with the default ParserOptions:
+//   (+ (+ (+ 1 2) 3) 4) => (+ 1 2 3 4), and (^ 2 (^ 3 4)) => (^ 2 3 4) since ^ folds to the right
+//   (- 0 x) => (- x), but only when the 0 was never written, as in (- x) or the infix -x
+//   Trees from Expr::to_exp have empty spans, so in them every such 0 counts as never written
+
+use crate::parser::{BinOp, Exp, Form, LetKind, Literal, Node, Program};
+use crate::BigInt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Printer {
+    pub width: usize, // Lines are kept this short where they can be, a name longer than this still gets printed
+    pub indent: usize, // How far the body of a let, lambda or define is indented under its opening paren
+    pub compact: bool, // Fill each broken line with operands, rather than putting each on its own line
+    pub resugar: bool, // Print k-ary and unary forms instead of the binary nodes they were desugared to
+}
+
+impl Default for Printer {
+    fn default() -> Printer {
+        Printer {
+            width: 80,
+            indent: 2,
+            compact: false,
+            resugar: false,
+        }
+    }
+}
+
+impl Printer {
+    pub fn new(width: usize) -> Printer {
+        Printer {
+            width,
+            ..Printer::default()
+        }
+    }
+
+    pub fn compact(width: usize) -> Printer {
+        Printer {
+            width,
+            compact: true,
+            ..Printer::default()
+        }
+    }
+
+    // The resugared exp on one line, (+ (+ 1 2) 3) => (+ 1 2 3)
+    pub fn sugared(exp: &dyn Exp) -> String {
+        Printer {
+            width: usize::MAX,
+            resugar: true,
+            ..Printer::default()
+        }
+        .print(exp)
+    }
+
+    // exp on as many lines as it needs, or error if it has an ErrorExp in it, like to_string
+    pub fn print(&self, exp: &dyn Exp) -> String {
+        let mut out = String::new();
+        match self.doc(exp) {
+            Some(doc) => {
+                self.layout(&doc, 0, 0, &mut out);
+            }
+            None => out.push_str("error"),
+        }
+        out
+    }
+
+    // Every form of program, each starting on a new line
+    pub fn print_program(&self, program: &Program) -> String {
+        let mut out = String::new();
+        for (i, form) in program.forms.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            let doc = match form {
+                Form::Define { name, value, .. } => self.doc(&**value).map(|value| {
+                    Doc::list(
+                        vec![Doc::text("define"), Doc::text(name), value],
+                        Style::Body(2),
+                    )
+                }),
+                Form::Exp(exp) => self.doc(&**exp),
+            };
+            match doc {
+                Some(doc) => {
+                    self.layout(&doc, 0, 0, &mut out);
+                }
+                None => out.push_str("error"),
+            }
+        }
+        out
+    }
+
+    // Whether doc goes on one line when it starts at column col
+    fn fits(&self, doc: &Doc, col: usize, trail: usize) -> bool {
+        col + doc.width() + trail <= self.width
+    }
+
+    // Writes doc starting at column col, with trail characters still to come on its last line
+    // Returns the column its last line ends at
+    fn layout(&self, doc: &Doc, col: usize, trail: usize, out: &mut String) -> usize {
+        let (items, style) = match doc {
+            Doc::List { items, style, .. } if !self.fits(doc, col, trail) => (items, *style),
+            _ => {
+                doc.flat(out);
+                return col + doc.width();
+            }
+        };
+        let last = items.len().saturating_sub(1);
+        let trail_after = |i| if i == last { trail + 1 } else { 0 };
+        // How many items go on the opening line, and the column the rest line up at
+        let (head, align) = match style {
+            Style::Call if items.len() > 1 && matches!(items[0], Doc::Text(_)) => {
+                let first = col + items[0].width() + 2;
+                if self.fits(&items[1], first, trail_after(1)) {
+                    (2, first)
+                } else {
+                    (1, col + self.indent)
+                }
+            }
+            Style::Call | Style::Column => (1, col + 1),
+            Style::Body(n) => (n, col + self.indent),
+        };
+        out.push('(');
+        let mut end = col + 1;
+        let mut broke = false; // Whether the item before took more than one line
+        for (i, item) in items.iter().enumerate() {
+            if i == 0 {
+                broke = !self.fits(item, end, trail_after(i));
+                end = self.layout(item, end, trail_after(i), out);
+            } else if i < head
+                || (self.compact && !broke && end + 1 + item.width() + trail_after(i) <= self.width)
+            {
+                broke = !self.fits(item, end + 1, trail_after(i));
+                out.push(' ');
+                end = self.layout(item, end + 1, trail_after(i), out);
+            } else {
+                broke = !self.fits(item, align, trail_after(i));
+                out.push('\n');
+                out.extend(std::iter::repeat_n(' ', align));
+                end = self.layout(item, align, trail_after(i), out);
+            }
+        }
+        out.push(')');
+        end + 1
+    }
+}
+
+// A tree of text to lay out, with the width of each list worked out once so laying out stays linear
+enum Doc {
+    Text(String),
+    List {
+        items: Vec<Doc>,
+        style: Style,
+        width: usize, // On one line
+    },
+}
+
+// How a list that doesn't fit breaks
+#[derive(Clone, Copy)]
+enum Style {
+    Call,        // (+ 1\n   2), the operands after the first line up under it
+    Body(usize), // (let ((x 1))\n  x), this many items on the opening line and the rest indented
+    Column,      // ((x 1)\n (y 2)), every item in a column, for let bindings and lambda parameters
+}
+
+impl Doc {
+    fn text(text: &str) -> Doc {
+        Doc::Text(text.to_string())
+    }
+
+    fn list(items: Vec<Doc>, style: Style) -> Doc {
+        let width = items.iter().map(Doc::width).sum::<usize>() + items.len().max(1) + 1;
+        Doc::List {
+            items,
+            style,
+            width,
+        }
+    }
+
+    fn width(&self) -> usize {
+        match self {
+            Doc::Text(text) => text.chars().count(),
+            Doc::List { width, .. } => *width,
+        }
+    }
+
+    fn flat(&self, out: &mut String) {
+        match self {
+            Doc::Text(text) => out.push_str(text),
+            Doc::List { items, .. } => {
+                out.push('(');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push(' ');
+                    }
+                    item.flat(out);
+                }
+                out.push(')');
+            }
+        }
+    }
+}
+
+impl Printer {
+    // The doc for exp, or None if it has an ErrorExp in it
+    fn doc(&self, exp: &dyn Exp) -> Option<Doc> {
+        let doc = match exp.node() {
+            Node::Lit(lit) => Doc::Text(lit.to_source()),
+            Node::Var(name) => Doc::text(name),
+            Node::Builtin(builtin) => Doc::text(&builtin.name),
+            Node::Binary(op, lhs, rhs) if self.resugar => {
+                let args = match unary_operand(op, lhs, rhs) {
+                    Some(operand) => vec![operand],
+                    None => operands(op, lhs, rhs),
+                };
+                self.call(op.symbol(), &args)?
+            }
+            Node::Binary(op, lhs, rhs) => self.call(op.symbol(), &[lhs, rhs])?,
+            Node::Compare(op, lhs, rhs) => self.call(op.symbol(), &[lhs, rhs])?,
+            Node::And(lhs, rhs) => self.call("and", &[lhs, rhs])?,
+            Node::Or(lhs, rhs) => self.call("or", &[lhs, rhs])?,
+            Node::Not(arg) => self.call("not", &[arg])?,
+            Node::If(cond, then, els) => self.call("if", &[cond, then, els])?,
+            Node::Let(kind, bindings, body) => {
+                let keyword = match kind {
+                    LetKind::Parallel => "let",
+                    LetKind::Sequential => "let*",
+                };
+                let bindings = bindings
+                    .iter()
+                    .map(|b| self.call(&b.name, &[&*b.value]))
+                    .collect::<Option<Vec<Doc>>>()?;
+                Doc::list(
+                    vec![
+                        Doc::text(keyword),
+                        Doc::list(bindings, Style::Column),
+                        self.doc(body)?,
+                    ],
+                    Style::Body(2),
+                )
+            }
+            Node::Lambda(params, body) => {
+                let params = params.iter().map(|p| Doc::text(&p.name)).collect();
+                Doc::list(
+                    vec![
+                        Doc::text("lambda"),
+                        Doc::list(params, Style::Column),
+                        self.doc(&**body)?,
+                    ],
+                    Style::Body(2),
+                )
+            }
+            Node::Call(func, args) => {
+                let mut items = vec![self.doc(func)?];
+                for arg in args {
+                    items.push(self.doc(&**arg)?);
+                }
+                Doc::list(items, Style::Call)
+            }
+            Node::Error => return None,
+        };
+        Some(doc)
+    }
+
+    fn call(&self, head: &str, args: &[&dyn Exp]) -> Option<Doc> {
+        let mut items = vec![Doc::text(head)];
+        for arg in args {
+            items.push(self.doc(*arg)?);
+        }
+        Some(Doc::list(items, Style::Call))
+    }
+}
+
+// The operands of the k-ary form that folds to lhs op rhs, walking down the chain of op in the direction it folds
+// A unary form in the chain ends it, so (- (- 0 x) 1) => (- (- x) 1)
+fn operands<'a>(op: BinOp, lhs: &'a dyn Exp, rhs: &'a dyn Exp) -> Vec<&'a dyn Exp> {
+    if op == BinOp::Pow {
+        let mut args = vec![lhs];
+        let mut rest = rhs;
+        while let Node::Binary(BinOp::Pow, lhs, rhs) = rest.node() {
+            args.push(lhs);
+            rest = rhs;
+        }
+        args.push(rest);
+        args
+    } else {
+        let mut args = vec![rhs];
+        let mut rest = lhs;
+        while let Node::Binary(inner, lhs, rhs) = rest.node() {
+            if inner != op || unary_operand(inner, lhs, rhs).is_some() {
+                break;
+            }
+            args.push(rhs);
+            rest = lhs;
+        }
+        args.push(rest);
+        args.reverse();
+        args
+    }
+}
+
+// x if lhs op rhs is the unary (op x) desugared, which puts a literal on the left that was never written,
+// so its span is empty
+fn unary_operand<'a>(op: BinOp, lhs: &dyn Exp, rhs: &'a dyn Exp) -> Option<&'a dyn Exp> {
+    let implied = match op {
+        BinOp::Plus | BinOp::Minus => 0,
+        BinOp::Mult | BinOp::Div | BinOp::Mod | BinOp::Pow => return None,
+    };
+    let written = lhs.span().start != lhs.span().end;
+    match lhs.node() {
+        Node::Lit(Literal::Int(n)) if !written && *n == BigInt::from(implied) => Some(rhs),
+        _ => None,
+    }
+}
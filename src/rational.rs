@@ -0,0 +1,129 @@
+// Exact rational numbers for eval_rational, always kept in lowest terms with a positive denominator
+//   (- 1 (^ 2 (- 3))) => 7/8
+//   (^ 6 2)           => 36, which converts to a BigInt since the denominator is 1
+
+use crate::BigInt;
+use num_rational::BigRational;
+use num_traits::Zero;
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Rational(BigRational);
+
+impl Rational {
+    // numer / denom reduced by their gcd, or None if denom is 0
+    pub fn new(numer: BigInt, denom: BigInt) -> Option<Rational> {
+        if denom.is_zero() {
+            None
+        } else {
+            Some(Rational(BigRational::new(numer, denom)))
+        }
+    }
+
+    pub fn from_integer(n: BigInt) -> Rational {
+        Rational(BigRational::from_integer(n))
+    }
+
+    pub fn numer(&self) -> &BigInt {
+        self.0.numer()
+    }
+
+    pub fn denom(&self) -> &BigInt {
+        self.0.denom()
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.0.is_integer()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    // The numerator, if the denominator is 1 so no information is lost
+    pub fn to_big_int(&self) -> Option<BigInt> {
+        if self.is_integer() {
+            Some(self.numer().clone())
+        } else {
+            None
+        }
+    }
+
+    // The shortest decimal that rounds to x, so 0.1 is 1/10 rather than the binary fraction nearest to it
+    // None for infinities and NaN
+    pub fn from_f64(x: f64) -> Option<Rational> {
+        if !x.is_finite() {
+            return None;
+        }
+        let text = format!("{:e}", x); // -1.5e-3
+        let (mantissa, exponent) = text.split_once('e')?;
+        let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let digits: BigInt = format!("{}{}", whole, fraction).parse().ok()?;
+        let exponent = exponent.parse::<i32>().ok()? - fraction.len() as i32;
+
+        let scale = BigInt::from(10).pow(exponent.unsigned_abs());
+        if exponent < 0 {
+            Rational::new(digits, scale)
+        } else {
+            Some(Rational::from_integer(digits * scale))
+        }
+    }
+
+    // The largest integer that is not greater than self
+    pub fn floor(&self) -> BigInt {
+        self.0.floor().to_integer()
+    }
+
+    // 1 / self, or None for 0
+    pub fn recip(&self) -> Option<Rational> {
+        Rational::new(self.denom().clone(), self.numer().clone())
+    }
+}
+
+impl From<BigInt> for Rational {
+    fn from(n: BigInt) -> Rational {
+        Rational::from_integer(n)
+    }
+}
+
+impl TryFrom<&Rational> for BigInt {
+    type Error = ();
+
+    fn try_from(r: &Rational) -> Result<BigInt, ()> {
+        r.to_big_int().ok_or(())
+    }
+}
+
+impl std::ops::Add for Rational {
+    type Output = Rational;
+
+    fn add(self, rhs: Rational) -> Rational {
+        Rational(self.0 + rhs.0)
+    }
+}
+
+impl std::ops::Sub for Rational {
+    type Output = Rational;
+
+    fn sub(self, rhs: Rational) -> Rational {
+        Rational(self.0 - rhs.0)
+    }
+}
+
+impl std::ops::Mul for Rational {
+    type Output = Rational;
+
+    fn mul(self, rhs: Rational) -> Rational {
+        Rational(self.0 * rhs.0)
+    }
+}
+
+// p/q, or just p when q is 1
+impl std::fmt::Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numer())
+        } else {
+            write!(f, "{}/{}", self.numer(), self.denom())
+        }
+    }
+}
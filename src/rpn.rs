@@ -0,0 +1,85 @@
+// Reverse Polish notation, where each operator comes after its two operands
+//   1 2 + 3 *   => (* (+ 1 2) 3)
+//   2 3 2 ^ ^   => (^ 2 (^ 3 2)), and 2 3 ^ 2 ^ is (^ (^ 2 3) 2)
+// The order of the tokens decides the tree on its own, so any parsed arithmetic tree converts to RPN and back
+// unchanged, k-ary and unary forms included: (- 2) was parsed as (- 0 2), which is 0 2 -
+// RPN has no negative literals, so the -3 a tree built from an Expr can have is written 0 3 -, which evaluates the
+// same but parses back to (- 0 3)
+// The tokens are the same as in the Polish notation, so parser::lex lexes RPN too
+
+use crate::parser::{self, Atoms, BinOp, Exp, Literal, Node, ParseError, Token};
+use num_bigint::Sign;
+use std::rc::Rc;
+
+// Parses tokens from parser::lex as a single RPN expression
+pub fn parse(ts: Vec<Token>) -> Result<Rc<dyn Exp>, ParseError> {
+    let mut stack: Vec<Rc<dyn Exp>> = vec![];
+    for tok in &ts {
+        match BinOp::from_symbol(tok.text) {
+            Some(op) => {
+                if stack.len() < 2 {
+                    return Err(ParseError::MissingOperands {
+                        op: tok.text.to_string(),
+                        span: tok.span,
+                    });
+                }
+                let rhs = stack.pop().unwrap();
+                let lhs = stack.pop().unwrap();
+                let span = lhs.span().to(tok.span);
+                stack.push(op.build(lhs, rhs, span));
+            }
+            None => stack.push(Atoms::parse(*tok)?),
+        }
+    }
+    match stack.len() {
+        0 => Err(ParseError::UnexpectedEof {
+            span: parser::eof_span(&ts),
+        }),
+        1 => Ok(stack.pop().unwrap()),
+        // Operands left over with no operator to combine them, the first of them is where one was missing
+        _ => Err(ParseError::TrailingTokens {
+            found: source_of(&ts, &*stack[1]),
+            span: stack[1].span(),
+        }),
+    }
+}
+
+// The text of the tokens exp was parsed from
+fn source_of(ts: &[Token], exp: &dyn Exp) -> String {
+    let span = exp.span();
+    let texts: Vec<&str> = ts
+        .iter()
+        .filter(|tok| tok.span.start >= span.start && tok.span.end <= span.end)
+        .map(|tok| tok.text)
+        .collect();
+    texts.join(" ")
+}
+
+// exp in RPN, or None if exp has something without an RPN form, such as a comparison, a let or an ErrorExp
+pub fn to_rpn(exp: &dyn Exp) -> Option<String> {
+    let mut out = vec![];
+    write(exp, &mut out)?;
+    Some(out.join(" "))
+}
+
+fn write(exp: &dyn Exp, out: &mut Vec<String>) -> Option<()> {
+    match exp.node() {
+        Node::Lit(Literal::Int(n)) if n.sign() == Sign::Minus => {
+            out.extend(["0".to_string(), (-n).to_string(), "-".to_string()])
+        }
+        Node::Lit(Literal::Int(n)) => out.push(n.to_string()),
+        Node::Lit(Literal::Float(x)) if x.is_sign_negative() => {
+            out.extend(["0".to_string(), format!("{:?}", -x), "-".to_string()])
+        }
+        Node::Lit(Literal::Float(x)) => out.push(format!("{:?}", x)),
+        Node::Var(name) => out.push(name.to_string()),
+        Node::Builtin(builtin) => out.push(builtin.name.clone()),
+        Node::Binary(op, lhs, rhs) => {
+            write(lhs, out)?;
+            write(rhs, out)?;
+            out.push(op.symbol().to_string());
+        }
+        _ => return None,
+    }
+    Some(())
+}
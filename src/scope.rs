@@ -0,0 +1,81 @@
+// Static checks on the names bound by let and lambda, without evaluating anything
+//   (let ((x 1)) (let ((x 2)) x))
+//   shadowing finds the inner x, which hides the outer one for the rest of the body
+
+use crate::parser::{Exp, LetKind, Node, Span};
+
+// A let binding or lambda parameter that hides another binding of the same name
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Shadowing {
+    pub name: String,
+    pub span: Span,     // The binding doing the shadowing
+    pub shadowed: Span, // The binding it hides
+}
+
+// Every binding in exp that shadows another, in the order they appear in the source
+pub fn shadowing(exp: &dyn Exp) -> Vec<Shadowing> {
+    let mut found = vec![];
+    walk(exp, &mut vec![], &mut found);
+    found
+}
+
+// scope holds the names bound around exp, innermost last
+fn walk(exp: &dyn Exp, scope: &mut Vec<(String, Span)>, found: &mut Vec<Shadowing>) {
+    match exp.node() {
+        Node::Lit(_) | Node::Var(_) | Node::Builtin(_) | Node::Error => {}
+        Node::Binary(_, lhs, rhs)
+        | Node::Compare(_, lhs, rhs)
+        | Node::And(lhs, rhs)
+        | Node::Or(lhs, rhs) => {
+            walk(lhs, scope, found);
+            walk(rhs, scope, found);
+        }
+        Node::Not(arg) => walk(arg, scope, found),
+        Node::Call(func, args) => {
+            walk(func, scope, found);
+            for arg in args {
+                walk(&**arg, scope, found);
+            }
+        }
+        Node::Lambda(params, body) => {
+            let outer = scope.len();
+            for param in params {
+                bind(&param.name, param.span, scope, found);
+            }
+            walk(&**body, scope, found);
+            scope.truncate(outer);
+        }
+        Node::If(cond, then, els) => {
+            walk(cond, scope, found);
+            walk(then, scope, found);
+            walk(els, scope, found);
+        }
+        Node::Let(kind, bindings, body) => {
+            let outer = scope.len();
+            for binding in bindings {
+                // let values only see the names around the let, let* values also see the ones before them
+                if kind == LetKind::Parallel {
+                    let mut around = scope[..outer].to_vec();
+                    walk(&*binding.value, &mut around, found);
+                } else {
+                    walk(&*binding.value, scope, found);
+                }
+                bind(&binding.name, binding.span, scope, found);
+            }
+            walk(body, scope, found);
+            scope.truncate(outer);
+        }
+    }
+}
+
+// Adds name to the scope, noting the binding it shadows if there is one
+fn bind(name: &str, span: Span, scope: &mut Vec<(String, Span)>, found: &mut Vec<Shadowing>) {
+    if let Some((_, shadowed)) = scope.iter().rev().find(|(n, _)| n == name) {
+        found.push(Shadowing {
+            name: name.to_string(),
+            span,
+            shadowed: *shadowed,
+        });
+    }
+    scope.push((name.to_string(), span));
+}
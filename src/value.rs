@@ -0,0 +1,419 @@
+// Evaluation to a Value, which is either a number or a boolean, so expressions can compare and branch
+//   (if (< 2 10) (* 2 2) 2) => 4
+//   (and (< 1 2) (= 1 2))   => false
+// Numbers are i64s with checked arithmetic, the same as try_eval
+// Variables are looked up in an Env supplied by the caller
+//   let mut env = Env::new();
+//   env.set("x", 3);
+//   ast.eval_with(&env)
+
+use crate::builtins::Builtin;
+use crate::number;
+use crate::parser::{BinOp, CmpOp, EvalError, Exp, Form, LetBinding, LetKind, Node, Param, Span};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Value {
+    Num(i64),
+    Bool(bool),
+    Closure(Rc<Closure>),
+    Builtin(Rc<Builtin>),
+}
+
+// A function made by evaluating a lambda, along with the variables that were in scope there
+pub struct Closure {
+    pub params: Vec<Param>,
+    pub body: Rc<dyn Exp>,
+    pub env: Env,
+    pub span: Span, // The lambda it was made from
+}
+
+// Two closures are only equal if they are the same closure, so = compares functions by identity
+impl PartialEq for Closure {
+    fn eq(&self, other: &Closure) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Eq for Closure {}
+
+impl std::hash::Hash for Closure {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::ptr::hash(self, state);
+    }
+}
+
+impl std::fmt::Debug for Closure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Closure")
+            .field("params", &self.params)
+            .field("body", &self.body.to_string())
+            .field("span", &self.span)
+            .finish_non_exhaustive()
+    }
+}
+
+impl std::fmt::Display for Closure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let params: Vec<&str> = self.params.iter().map(|p| p.name.as_str()).collect();
+        write!(
+            f,
+            "(lambda ({}) {})",
+            params.join(" "),
+            self.body.to_string()
+        )
+    }
+}
+
+impl Value {
+    // What kind of value this is, for error messages
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Num(_) => "a number",
+            Value::Bool(_) => "a boolean",
+            Value::Closure(_) | Value::Builtin(_) => "a function",
+        }
+    }
+
+    // The number, or a TypeMismatch pointing at span, the expression the value came from
+    pub fn to_num(&self, span: Span) -> Result<i64, EvalError> {
+        match self {
+            Value::Num(n) => Ok(*n),
+            _ => Err(mismatch("a number", self, span)),
+        }
+    }
+
+    // The boolean, or a TypeMismatch pointing at span, the expression the value came from
+    pub fn to_bool(&self, span: Span) -> Result<bool, EvalError> {
+        match self {
+            Value::Bool(b) => Ok(*b),
+            _ => Err(mismatch("a boolean", self, span)),
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Num(n) => write!(f, "{}", n),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Closure(closure) => write!(f, "{}", closure),
+            Value::Builtin(builtin) => write!(f, "{}", builtin.name),
+        }
+    }
+}
+
+impl From<i64> for Value {
+    fn from(n: i64) -> Value {
+        Value::Num(n)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Value {
+        Value::Bool(b)
+    }
+}
+
+// The variables an expression is evaluated with, by name
+// Bindings are shared between clones, so a let can extend its scope without copying the ones around it
+// Names bound with define are global: they aren't captured by closures, but looked up wherever a name isn't
+// bound locally, which is how a defined function can call itself
+#[derive(Clone, Debug, Default)]
+pub struct Env {
+    head: Option<Rc<Binding>>, // The most recent binding, which shadows any earlier one with the same name
+    globals: Option<Rc<HashMap<String, Value>>>,
+}
+
+#[derive(Debug)]
+struct Binding {
+    name: String,
+    value: Value,
+    next: Option<Rc<Binding>>,
+}
+
+impl Env {
+    pub fn new() -> Env {
+        Env::default()
+    }
+
+    // Binds name to value, shadowing whatever it was bound to before
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<Value>) {
+        self.head = Some(Rc::new(Binding {
+            name: name.into(),
+            value: value.into(),
+            next: self.head.take(),
+        }));
+    }
+
+    // Binds name globally, like a define at the top level of a program
+    pub fn define(&mut self, name: impl Into<String>, value: impl Into<Value>) {
+        let globals = self.globals.get_or_insert_with(Default::default);
+        Rc::make_mut(globals).insert(name.into(), value.into());
+    }
+
+    pub fn get(&self, name: &str) -> Option<Value> {
+        let mut binding = self.head.as_deref();
+        while let Some(b) = binding {
+            if b.name == name {
+                return Some(b.value.clone());
+            }
+            binding = b.next.as_deref();
+        }
+        self.globals.as_ref()?.get(name).cloned()
+    }
+
+    // The local bindings without the globals, which is what a closure keeps
+    // A closure in the globals that kept them would keep itself alive, and miss any later defines
+    fn locals(&self) -> Env {
+        Env {
+            head: self.head.clone(),
+            globals: None,
+        }
+    }
+}
+
+impl<S: Into<String>, V: Into<Value>> FromIterator<(S, V)> for Env {
+    fn from_iter<I: IntoIterator<Item = (S, V)>>(iter: I) -> Env {
+        let mut env = Env::new();
+        for (name, value) in iter {
+            env.set(name, value);
+        }
+        env
+    }
+}
+
+fn mismatch(expected: &str, found: &Value, span: Span) -> EvalError {
+    EvalError::TypeMismatch {
+        expected: expected.to_string(),
+        found: found.type_name().to_string(),
+        span,
+    }
+}
+
+pub fn eval(exp: &dyn Exp, env: &Env) -> Result<Value, EvalError> {
+    match exp.node() {
+        // Arithmetic can't end in a call, so it skips the trampoline, which keeps deep expressions off the stack
+        Node::Binary(op, lhs, rhs) => arith(op, lhs, rhs, exp.span(), env).map(Value::Num),
+        node => eval_node(node, exp.span(), env),
+    }
+}
+
+// Evaluates the expression node, which was parsed from span, with the variables in env
+// Calls in tail position come back here as a Step rather than recursing, so a function that keeps calling
+// itself or others in tail position runs in constant stack however many calls it makes
+pub fn eval_node(node: Node<'_>, span: Span, env: &Env) -> Result<Value, EvalError> {
+    let mut next = step(node, span, env)?;
+    loop {
+        match next {
+            Step::Done(value) => return Ok(value),
+            Step::Call(closure, scope) => {
+                next = step(closure.body.node(), closure.body.span(), &scope)?;
+            }
+        }
+    }
+}
+
+// What's left of an evaluation after one step
+enum Step {
+    Done(Value),
+    Call(Rc<Closure>, Env), // A call in tail position, the closure's body still has to be evaluated in the Env
+}
+
+// Evaluates node up to its tail call, if it ends in one
+fn step(node: Node<'_>, span: Span, env: &Env) -> Result<Step, EvalError> {
+    let value = match node {
+        Node::Lit(lit) => number::literal(lit, span).map(Value::Num)?,
+        Node::Var(name) => env.get(name).ok_or_else(|| EvalError::UnboundVariable {
+            name: name.to_string(),
+            span,
+        })?,
+        Node::Binary(op, lhs, rhs) => Value::Num(arith(op, lhs, rhs, span, env)?),
+        Node::Compare(op, lhs, rhs) => compare(op, lhs, rhs, env)?,
+        // and and or only evaluate rhs when lhs doesn't already decide the result
+        Node::And(lhs, rhs) => Value::Bool(
+            eval(lhs, env)?.to_bool(lhs.span())? && eval(rhs, env)?.to_bool(rhs.span())?,
+        ),
+        Node::Or(lhs, rhs) => Value::Bool(
+            eval(lhs, env)?.to_bool(lhs.span())? || eval(rhs, env)?.to_bool(rhs.span())?,
+        ),
+        Node::Not(arg) => Value::Bool(!eval(arg, env)?.to_bool(arg.span())?),
+        // The branches of an if and the body of a let are in tail position
+        Node::If(cond, then, els) => {
+            let branch = if eval(cond, env)?.to_bool(cond.span())? {
+                then
+            } else {
+                els
+            };
+            return step(branch.node(), branch.span(), env);
+        }
+        Node::Let(kind, bindings, body) => {
+            let scope = bind_let(kind, bindings, env)?;
+            return step(body.node(), body.span(), &scope);
+        }
+        Node::Lambda(params, body) => lambda(params, body, span, env),
+        Node::Call(func, args) => return call(func, args, span, env),
+        // A name the caller put in the Env hides the builtin, so formulas can use variables such as max
+        Node::Builtin(builtin) => env
+            .get(&builtin.name)
+            .unwrap_or_else(|| Value::Builtin(Rc::clone(builtin))),
+        Node::Error => return Err(EvalError::ErrorNode { span }),
+    };
+    Ok(Step::Done(value))
+}
+
+// The less common forms are evaluated out of line, which keeps step's stack frame small for deep expressions
+
+// Nested arithmetic is evaluated with a Vec of pending operations rather than by recursing, since a flat
+// (+ 1 1 ...) with a million operands is a chain of a million operators
+fn arith(op: BinOp, lhs: &dyn Exp, rhs: &dyn Exp, span: Span, env: &Env) -> Result<i64, EvalError> {
+    let mut tasks = vec![
+        Arith::Apply(op, rhs.span(), span),
+        Arith::Eval(rhs),
+        Arith::Eval(lhs),
+    ];
+    let mut operands = vec![];
+    while let Some(next) = tasks.pop() {
+        match next {
+            Arith::Eval(exp) => match exp.node() {
+                Node::Binary(op, lhs, rhs) => tasks.extend([
+                    Arith::Apply(op, rhs.span(), exp.span()),
+                    Arith::Eval(rhs),
+                    Arith::Eval(lhs),
+                ]),
+                node => operands.push(eval_node(node, exp.span(), env)?.to_num(exp.span())?),
+            },
+            Arith::Apply(op, rhs_span, span) => {
+                let r = operands
+                    .pop()
+                    .expect("an operator comes after both of its operands");
+                let l = operands
+                    .pop()
+                    .expect("an operator comes after both of its operands");
+                operands.push(number::apply(op, l, r, rhs_span, span)?);
+            }
+        }
+    }
+    Ok(operands.pop().expect("the last operator leaves its result"))
+}
+
+// What's left to do in arith: an operand to evaluate, or an operator to apply to the last two results, with the
+// spans of its right operand and of the whole operation
+enum Arith<'a> {
+    Eval(&'a dyn Exp),
+    Apply(BinOp, Span, Span),
+}
+
+fn compare(op: CmpOp, lhs: &dyn Exp, rhs: &dyn Exp, env: &Env) -> Result<Value, EvalError> {
+    let l = eval(lhs, env)?;
+    let r = eval(rhs, env)?;
+    let result = match op {
+        // Any two values of the same type can be tested for equality
+        CmpOp::Eq if l.type_name() == r.type_name() => l == r,
+        CmpOp::Eq => return Err(mismatch(l.type_name(), &r, rhs.span())),
+        _ => {
+            let (l, r) = (l.to_num(lhs.span())?, r.to_num(rhs.span())?);
+            match op {
+                CmpOp::Lt => l < r,
+                CmpOp::Le => l <= r,
+                CmpOp::Gt => l > r,
+                _ => l >= r,
+            }
+        }
+    };
+    Ok(Value::Bool(result))
+}
+
+// The scope a let's body is evaluated in
+fn bind_let(kind: LetKind, bindings: &[LetBinding], env: &Env) -> Result<Env, EvalError> {
+    let mut scope = env.clone();
+    for binding in bindings {
+        // let evaluates every value in the surrounding scope, let* in the scope built so far
+        let value = match kind {
+            LetKind::Parallel => eval(&*binding.value, env)?,
+            LetKind::Sequential => eval(&*binding.value, &scope)?,
+        };
+        scope.set(binding.name.as_str(), value);
+    }
+    Ok(scope)
+}
+
+fn lambda(params: &[Param], body: &Rc<dyn Exp>, span: Span, env: &Env) -> Value {
+    Value::Closure(Rc::new(Closure {
+        params: params.to_vec(),
+        body: Rc::clone(body),
+        env: env.locals(),
+        span,
+    }))
+}
+
+// Evaluates the function and arguments, leaving the body for eval_node to evaluate
+fn call(func: &dyn Exp, args: &[Rc<dyn Exp>], span: Span, env: &Env) -> Result<Step, EvalError> {
+    let closure = match eval(func, env)? {
+        Value::Closure(closure) => closure,
+        Value::Builtin(builtin) => return call_builtin(&builtin, args, span, env).map(Step::Done),
+        other => return Err(mismatch("a function", &other, func.span())),
+    };
+    if args.len() != closure.params.len() {
+        return Err(EvalError::ArityMismatch {
+            expected: closure.params.len(),
+            found: args.len(),
+            span,
+            defined: closure.span,
+        });
+    }
+    // The body sees the closure's variables, not the caller's, plus the arguments and the globals
+    let mut scope = closure.env.clone();
+    scope.globals = env.globals.clone();
+    for (param, arg) in closure.params.iter().zip(args) {
+        scope.set(param.name.as_str(), eval(&**arg, env)?);
+    }
+    Ok(Step::Call(closure, scope))
+}
+
+// Builtins have no body to evaluate, so calling one is never a tail call
+fn call_builtin(
+    builtin: &Builtin,
+    args: &[Rc<dyn Exp>],
+    span: Span,
+    env: &Env,
+) -> Result<Value, EvalError> {
+    // The parser checks calls by name, this catches the ones through a variable
+    if !builtin.arity.accepts(args.len()) {
+        return Err(EvalError::WrongArgumentCount {
+            name: builtin.name.clone(),
+            arity: builtin.arity,
+            found: args.len(),
+            span,
+        });
+    }
+    let args = args
+        .iter()
+        .map(|arg| eval(&**arg, env))
+        .collect::<Result<Vec<_>, _>>()?;
+    // A builtin doesn't know where its arguments came from, so errors without a span point at the call
+    builtin.call(&args).map_err(|err| {
+        if err.span() == Span::default() {
+            err.with_span(span)
+        } else {
+            err
+        }
+    })
+}
+
+// Runs the forms of a program in order, returning the value of each expression
+pub fn eval_program(forms: &[Form], env: &Env) -> Result<Vec<Value>, EvalError> {
+    let mut env = env.clone();
+    let mut values = vec![];
+    for form in forms {
+        match form {
+            Form::Define { name, value, .. } => {
+                let value = eval(&**value, &env)?;
+                env.define(name.as_str(), value);
+            }
+            Form::Exp(exp) => values.push(eval(&**exp, &env)?),
+        }
+    }
+    Ok(values)
+}
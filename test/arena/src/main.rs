@@ -0,0 +1,143 @@
+use project::arena;
+use project::parser;
+use project::{BigInt, Env, Rational};
+use std::io::Write;
+
+fn main() {
+    let inputs = [
+        "(+ 1 (* 2 3))",
+        "(- 0 1 2 3)",
+        "(- 2)",
+        "(/ 2)",
+        "(+ 3)",
+        "(^ 2 3 2)",
+        "(% (- 7) 2)",
+        "+ 1 * 2 3",
+        "(* x (+ y 1))",
+        "flag",
+        "(+ 1 flag)",
+        "(+ 1.5 2.5)",
+        "(/ 1 0)",
+        "(^ 2 (- 1))",
+        "(* 9223372036854775807 2)",
+        "(+ 99999999999999999999 1)",
+        "(< 1 2)",
+        "(max 1 2)",
+        "(let ((x 1)) x)",
+        "(* 2)",
+        "(+ 1 2",
+        "(+ 1 2))",
+        "()",
+        "(+ 1 @)",
+        "(+ 1 ٣)",
+        "(+ 1 ٣٣٣٣٣٣٣٣٣٣٣٣٣٣٣٣٣٣٣٣)",
+        "",
+    ];
+
+    let mut env = Env::new();
+    env.set("x", 2);
+    env.set("y", 3);
+    env.set("flag", true);
+
+    let f = std::fs::OpenOptions::new()
+        .write(true)
+        .append(true)
+        .create(true)
+        .open("output")
+        .expect("unable to open output file");
+    let mut f = std::io::BufWriter::new(f);
+
+    let show = |r: Result<String, parser::EvalError>| match r {
+        Ok(v) => v,
+        Err(e) => e.to_string(),
+    };
+
+    for input in inputs {
+        let rc = parser::try_parse(parser::lex(input));
+        match arena::try_parse(parser::lex(input)) {
+            Ok(ast) => {
+                let rc = rc.unwrap();
+                let value = show(ast.eval_with(&env).map(|v| v.to_string()));
+                let big = show(ast.eval_big().map(|n| n.to_string()));
+                let rational = show(ast.eval_rational().map(|q| q.to_string()));
+                let float = show(ast.eval_f64().map(|x| x.to_string()));
+                // Everything agrees with the Rc tree the parser builds, errors and spans included
+                let same = ast.to_string() == rc.to_string()
+                    && value == show(rc.eval_with(&env).map(|v| v.to_string()))
+                    && big == show(rc.eval_big().map(|n| n.to_string()))
+                    && rational == show(rc.eval_rational().map(|q| q.to_string()))
+                    && float == show(rc.eval_f64().map(|x| x.to_string()))
+                    && ast.eval() == rc.eval()
+                    && ast.to_exp().span() == rc.span();
+                writeln!(
+                    f,
+                    "{:?} {} {} {:?} {:?} {:?} {:?} {}",
+                    input,
+                    ast.len(),
+                    ast,
+                    value,
+                    big,
+                    rational,
+                    float,
+                    same
+                )
+                .expect("Unable to write");
+            }
+            Err(e) => {
+                let same = rc.err().map(|rc| rc.to_string()) == Some(e.to_string());
+                writeln!(
+                    f,
+                    "{:?} {:?} {}",
+                    input,
+                    e.to_string(),
+                    same || matches!(e, parser::ParseError::NotArithmetic { .. })
+                )
+                .expect("Unable to write")
+            }
+        }
+    }
+
+    // Nodes refer to their operands by index
+    let ast = arena::try_parse(parser::lex("(* (+ 1 2) 3)")).unwrap();
+    if let arena::Node::Binary(op, lhs, rhs) = ast.node(ast.root()) {
+        writeln!(
+            f,
+            "{:?} {:?} {:?} {:?}",
+            op,
+            ast.node(*lhs),
+            ast.node(*rhs),
+            ast.span(*lhs)
+        )
+        .expect("Unable to write");
+    }
+
+    // A chain far deeper than the Rc tree could evaluate without a bigger stack
+    let mut input = String::from("(-");
+    for i in 0..50_000 {
+        input.push_str(&format!(" {}", i));
+    }
+    input.push(')');
+    let ast = arena::try_parse(parser::lex(&input)).unwrap();
+    writeln!(
+        f,
+        "{} {:?} {:?}",
+        ast.len(),
+        ast.eval_value().map(|v| v.to_string()),
+        ast.eval_as::<BigInt>()
+    )
+    .expect("Unable to write");
+    let mut input = String::from("(^");
+    for _ in 0..20_000 {
+        input.push_str(" 1");
+    }
+    input.push(')');
+    let ast = arena::try_parse(parser::lex(&input)).unwrap();
+    writeln!(
+        f,
+        "{:?} {:?}",
+        ast.try_eval(),
+        ast.eval_as::<Rational>().map(|q| q.to_string())
+    )
+    .expect("Unable to write");
+    writeln!(f, "{}", ast.to_string().len()).expect("Unable to write");
+}
@@ -0,0 +1,108 @@
+use project::builtins::Arity;
+use project::parser::{self, Span};
+use project::{Builtins, Env, Value};
+use std::io::Write;
+
+fn main() {
+    let inputs = [
+        "(min 4 2 8)",
+        "(max 4 2 8)",
+        "(min 5)",
+        "(abs (- 7))",
+        "(gcd 12 18)",
+        "(gcd (- 12) 18 8)",
+        "(gcd 0 0)",
+        "(clamp 15 0 10)",
+        "(clamp (- 5) 0 10)",
+        "(clamp 5 0 10)",
+        "(+ 1 (max 2 (abs (- 9))))",
+        // Names bound by a let or lambda hide the builtin
+        "(let ((max 3)) (+ max 1))",
+        "(let ((abs (lambda (x y) (+ x y)))) (abs 1 2))",
+        "((lambda (min) (min 1 2)) (lambda (a b) (- a b)))",
+        // Builtins are values, like any other function
+        "(let ((f max)) (f 1 9 3))",
+        "(let ((f abs)) (f 1 2))",
+        "max",
+        "(= min min)",
+        "(abs (- 0 9223372036854775807 1))",
+        "(max 1 (< 1 2))",
+        "(abs 1 2)",
+        "(min)",
+        "(clamp 1 2)",
+    ];
+
+    let f = std::fs::OpenOptions::new()
+        .write(true)
+        .append(true)
+        .create(true)
+        .open("output")
+        .expect("unable to open output file");
+    let mut f = std::io::BufWriter::new(f);
+
+    for input in inputs {
+        match parser::try_parse(parser::lex(input)) {
+            Ok(ast) => {
+                let value = match ast.eval_value() {
+                    Ok(v) => v.to_string(),
+                    Err(e) => format!("{} at {}:{}", e, e.span().line, e.span().col),
+                };
+                writeln!(f, "{:?} {:?} {:?}", input, ast.to_string(), value)
+                    .expect("Unable to write");
+            }
+            Err(e) => writeln!(f, "{:?} {:?}", input, e.to_string()).expect("Unable to write"),
+        }
+    }
+
+    // A defined name hides the builtin in every form of the program, even the ones before the define
+    let program = parser::parse_program(parser::lex(
+        "(define (twice x) (* 2 (abs x))) (define (abs x) x) (twice (- 3)) (abs (- 3))",
+    ))
+    .unwrap();
+    let values: Vec<String> = program.eval_all().unwrap().iter().map(|v| v.to_string()).collect();
+    writeln!(f, "{:?}", values).expect("Unable to write");
+
+    // Hosts can register their own functions, and leave out the default ones
+    let mut builtins = Builtins::new();
+    builtins.register("double", Arity::Exact(1), |args| {
+        Ok(Value::Num(args[0].to_num(Span::default())? * 2))
+    });
+    builtins.register("sum", Arity::AtLeast(0), |args| {
+        let mut total = 0;
+        for arg in args {
+            total += arg.to_num(Span::default())?;
+        }
+        Ok(Value::Num(total))
+    });
+    for input in ["(double 21)", "(sum)", "(sum 1 2 3 (double 2))", "(double 1 2)", "(max 1 2)", "(double (= 1 1))"] {
+        let result = match parser::try_parse_with(parser::lex(input), &builtins) {
+            Ok(ast) => match ast.eval_with(&Env::new()) {
+                Ok(v) => v.to_string(),
+                Err(e) => e.to_string(),
+            },
+            Err(e) => e.to_string(),
+        };
+        writeln!(f, "{:?} {:?}", input, result).expect("Unable to write");
+    }
+
+    // Variables in the Env hide the builtins of the same name
+    let mut env = Env::new();
+    env.set("max", 3);
+    env.set("min", 1);
+    for input in ["(+ max 1)", "(- max min)", "(abs (- max 5))", "(max 1 2)"] {
+        let result = match parser::try_parse(parser::lex(input)) {
+            Ok(ast) => match ast.eval_with(&env) {
+                Ok(v) => v.to_string(),
+                Err(e) => e.to_string(),
+            },
+            Err(e) => e.to_string(),
+        };
+        writeln!(f, "{:?} {:?}", input, result).expect("Unable to write");
+    }
+
+    // The default set
+    let defaults = Builtins::default();
+    let mut names: Vec<&str> = defaults.names().collect();
+    names.sort();
+    writeln!(f, "{:?}", names).expect("Unable to write");
+}
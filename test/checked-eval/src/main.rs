@@ -0,0 +1,24 @@
+use project::parser;
+use std::io::Write;
+
+fn main() {
+    let inputs = ["(- 0 1000000)", "(^ 2 (- 1))", "(^ 2 62)", "(^ 2 63)", "(* (^ 2 40) (^ 2 30))", "(+ 1 (^ 1))"];
+
+    let f = std::fs::OpenOptions::new()
+        .write(true)
+        .append(true)
+        .create(true)
+        .open("output")
+        .expect("unable to open output file");
+    let mut f = std::io::BufWriter::new(f);
+
+    for input in inputs {
+        let tokens = parser::lex(input);
+        let ast = parser::parse(tokens);
+        let evaled_str = match ast.try_eval() {
+            Ok(n) => n.to_string(),
+            Err(e) => e.to_string(),
+        };
+        writeln!(f, "{:?}", evaled_str).expect("Unable to write");
+    }
+}
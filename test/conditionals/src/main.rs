@@ -0,0 +1,69 @@
+use project::parser;
+use std::io::Write;
+
+fn main() {
+    let inputs = [
+        "(if (< 5 10) (* 5 2) 5)",
+        "(if (< 50 10) (* 50 2) 50)",
+        "(and (< 1 2) (>= 2 2) (= 3 3))",
+        "(or (> 1 2) (<= 3 2))",
+        "(not (= (< 1 2) (< 2 1)))",
+        "(and (= 1 2) (= (/ 1 0) 1))",
+        "(or (< 1 2) (^ 2 (- 1)))",
+        "(if (> 1 2) (/ 1 0) 7)",
+        "(+ 1 (< 1 2))",
+        "(if 1 2 3)",
+        "(= 1 (< 1 2))",
+        "(< 1 2 3)",
+        "(if (< 1 2) 3)",
+        "(and (< 1 2))",
+        "(if (< 0.5 1) 1 2)",
+        "(if (= (/ 1 3) (/ 2 6)) 1 0)",
+        "(if (> (^ 2 100) (^ 2 99)) 1 0)",
+    ];
+
+    let f = std::fs::OpenOptions::new()
+        .write(true)
+        .append(true)
+        .create(true)
+        .open("output")
+        .expect("unable to open output file");
+    let mut f = std::io::BufWriter::new(f);
+
+    for input in inputs {
+        let tokens = parser::lex(input);
+        let ast = match parser::try_parse(tokens) {
+            Ok(ast) => ast,
+            Err(e) => {
+                writeln!(f, "{:?}", e.to_string()).expect("Unable to write");
+                continue;
+            }
+        };
+        let evaled_str = match ast.eval_value() {
+            Ok(v) => v.to_string(),
+            Err(e) => e.to_string(),
+        };
+        let big_str = match ast.eval_big() {
+            Ok(n) => n.to_string(),
+            Err(e) => e.to_string(),
+        };
+        let rational_str = match ast.eval_rational() {
+            Ok(n) => n.to_string(),
+            Err(e) => e.to_string(),
+        };
+        let f64_str = match ast.eval_f64() {
+            Ok(n) => n.to_string(),
+            Err(e) => e.to_string(),
+        };
+        writeln!(
+            f,
+            "{:?} {:?} {:?} {:?} {:?}",
+            ast.to_string(),
+            evaled_str,
+            big_str,
+            rational_str,
+            f64_str
+        )
+        .expect("Unable to write");
+    }
+}
@@ -0,0 +1,26 @@
+use project::diagnostics::Diagnostic;
+use project::parser;
+use std::io::Write;
+
+fn main() {
+    let input = "(+ (* 2 3 4)
+   (- 2 3 2)
+   (^ 1)
+)";
+
+    let tokens = parser::lex(&input);
+    let rendered = match parser::try_parse(tokens) {
+        Ok(ast) => ast.to_string(),
+        Err(e) => Diagnostic::from(&e).render(input),
+    };
+
+    let f = std::fs::OpenOptions::new()
+        .write(true)
+        .append(true)
+        .create(true)
+        .open("output")
+        .expect("unable to open output file");
+    let mut f = std::io::BufWriter::new(f);
+
+    write!(f, "{}", rendered).expect("Unable to write");
+}
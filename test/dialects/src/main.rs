@@ -0,0 +1,84 @@
+use project::parser::{self, Dialect, ParserOptions, UnaryStyle};
+use std::io::Write;
+
+fn main() {
+    let inputs = [
+        "(+ 3)",
+        "(- 2)",
+        "(/ 2)",
+        "(* 4)",
+        "(^ 5)",
+        "+ 1 25",
+        "- + 3 2 1",
+        "(- 3 2 1)",
+        "(^ 3 2 1)",
+        "(+ 1 (+ 2 (+ 3 4)))",
+        "(and (< 1 2) (< 2 3) (< 3 4))",
+    ];
+
+    let no_unary = ParserOptions {
+        dialect: Dialect {
+            unary: UnaryStyle::Disallowed,
+            ..Dialect::default()
+        },
+        ..ParserOptions::default()
+    };
+    let parens_only = ParserOptions {
+        dialect: Dialect {
+            bare_binary: false,
+            ..Dialect::default()
+        },
+        ..ParserOptions::default()
+    };
+    let binary_only = ParserOptions {
+        dialect: Dialect {
+            kary: false,
+            ..Dialect::default()
+        },
+        ..ParserOptions::default()
+    };
+    let shallow = ParserOptions {
+        dialect: Dialect {
+            max_depth: Some(2),
+            ..Dialect::default()
+        },
+        ..ParserOptions::default()
+    };
+    let dialects = [
+        ("default", ParserOptions::default()),
+        ("strict_spec", ParserOptions::strict_spec()),
+        ("no_unary", no_unary),
+        ("parens_only", parens_only),
+        ("binary_only", binary_only),
+        ("shallow", shallow),
+    ];
+
+    let f = std::fs::OpenOptions::new()
+        .write(true)
+        .append(true)
+        .create(true)
+        .open("output")
+        .expect("unable to open output file");
+    let mut f = std::io::BufWriter::new(f);
+
+    for (name, options) in &dialects {
+        for input in inputs {
+            let result = match parser::try_parse_with_options(parser::lex(input), options) {
+                Ok(ast) => match ast.eval_value() {
+                    Ok(v) => format!("{} => {}", ast.to_string(), v),
+                    Err(e) => format!("{} => {}", ast.to_string(), e),
+                },
+                Err(e) => e.to_string(),
+            };
+            writeln!(f, "{} {:?} {:?}", name, input, result).expect("Unable to write");
+        }
+    }
+
+    // Programs are parsed with the same options
+    let program = parser::parse_program_with_options(
+        parser::lex("(define (inc x) (+ x 1)) (inc (+ 3))"),
+        &ParserOptions::strict_spec(),
+    )
+    .unwrap();
+    writeln!(f, "{:?}", program.to_string()).expect("Unable to write");
+}
@@ -0,0 +1,46 @@
+use project::parser;
+use std::io::Write;
+
+fn main() {
+    let inputs = [
+        "(/ 7 2)",
+        "(% 7 2)",
+        "(/ (- 7) 2)",
+        "(% (- 7) 2)",
+        "(% 7 (- 2))",
+        "(/ 100 2 5)",
+        "/ 9 % 7 4",
+        "(/ 1 (- 2 2))",
+        "(% 5 0)",
+        "(% 5)",
+    ];
+
+    let f = std::fs::OpenOptions::new()
+        .write(true)
+        .append(true)
+        .create(true)
+        .open("output")
+        .expect("unable to open output file");
+    let mut f = std::io::BufWriter::new(f);
+
+    for input in inputs {
+        let tokens = parser::lex(input);
+        let ast = match parser::try_parse(tokens) {
+            Ok(ast) => ast,
+            Err(e) => {
+                writeln!(f, "{:?}", e.to_string()).expect("Unable to write");
+                continue;
+            }
+        };
+        let evaled_str = match ast.try_eval() {
+            Ok(n) => n.to_string(),
+            Err(e) => e.to_string(),
+        };
+        let rational_str = match ast.eval_as::<project::Rational>() {
+            Ok(n) => n.to_string(),
+            Err(e) => e.to_string(),
+        };
+        writeln!(f, "{:?} {:?} {:?}", ast.to_string(), evaled_str, rational_str)
+            .expect("Unable to write");
+    }
+}
@@ -0,0 +1,144 @@
+use project::expr::Float;
+use project::parser;
+use project::{BigInt, Expr};
+use std::collections::HashSet;
+use std::io::Write;
+
+// A pass written as a match: folds arithmetic on two integer literals into one literal
+fn fold_constants(expr: &Expr) -> Expr {
+    let both = |lhs: &Expr, rhs: &Expr| match (fold_constants(lhs), fold_constants(rhs)) {
+        (Expr::Lit(a), Expr::Lit(b)) => Ok((a, b)),
+        (lhs, rhs) => Err((Box::new(lhs), Box::new(rhs))),
+    };
+    match expr {
+        Expr::Plus(lhs, rhs) => match both(lhs, rhs) {
+            Ok((a, b)) => Expr::Lit(a + b),
+            Err((lhs, rhs)) => Expr::Plus(lhs, rhs),
+        },
+        Expr::Minus(lhs, rhs) => match both(lhs, rhs) {
+            Ok((a, b)) => Expr::Lit(a - b),
+            Err((lhs, rhs)) => Expr::Minus(lhs, rhs),
+        },
+        Expr::Mult(lhs, rhs) => match both(lhs, rhs) {
+            Ok((a, b)) => Expr::Lit(a * b),
+            Err((lhs, rhs)) => Expr::Mult(lhs, rhs),
+        },
+        Expr::Let(kind, bindings, body) => Expr::Let(
+            *kind,
+            bindings
+                .iter()
+                .map(|(name, value)| (name.clone(), fold_constants(value)))
+                .collect(),
+            Box::new(fold_constants(body)),
+        ),
+        other => other.clone(),
+    }
+}
+
+// Every subtree, to find the ones that repeat
+fn subtrees<'a>(expr: &'a Expr, out: &mut Vec<&'a Expr>) {
+    out.push(expr);
+    match expr {
+        Expr::Plus(lhs, rhs)
+        | Expr::Minus(lhs, rhs)
+        | Expr::Mult(lhs, rhs)
+        | Expr::Div(lhs, rhs)
+        | Expr::Mod(lhs, rhs)
+        | Expr::Pow(lhs, rhs) => {
+            subtrees(lhs, out);
+            subtrees(rhs, out);
+        }
+        _ => {}
+    }
+}
+
+fn main() {
+    let inputs = [
+        "(+ 1 (* 2 3))",
+        "(let ((x (- 10 4))) (* x (+ 1 1)))",
+        "(- 2)",
+        "(^ 2 3 2)",
+        "(if (and (< 1 2) (not (= 1 2))) (or (> 1 2) (>= 2 2)) (<= 1 2))",
+        "((lambda (a b) (* a b)) 6 (+ 3 4))",
+        "(max 1 (abs (- 5)) 1.5e3)",
+        "(+ 1 @)",
+    ];
+
+    let f = std::fs::OpenOptions::new()
+        .write(true)
+        .append(true)
+        .create(true)
+        .open("output")
+        .expect("unable to open output file");
+    let mut f = std::io::BufWriter::new(f);
+
+    for input in inputs {
+        let ast = parser::parse(parser::lex(input));
+        let expr = Expr::from(&ast);
+        let back = expr.to_exp();
+        let folded = fold_constants(&expr);
+        let value = match folded.to_exp().eval_value() {
+            Ok(v) => v.to_string(),
+            Err(e) => e.to_string(),
+        };
+        writeln!(
+            f,
+            "{:?} {} {} {:?} {:?}",
+            input,
+            back.to_string() == ast.to_string(),
+            expr == Expr::from(&back),
+            folded.to_string(),
+            value
+        )
+        .expect("Unable to write");
+    }
+
+    // The same shape from different source is equal, and hashes the same
+    let a = Expr::from(&parser::parse(parser::lex("(+ 1 2 3)")));
+    let b = Expr::from(&parser::parse(parser::lex("(+   (+ 1 2)\n 3)")));
+    let c = Expr::from(&parser::parse(parser::lex("(+ 1 (+ 2 3))")));
+    let set: HashSet<Expr> = vec![a.clone(), b.clone(), c.clone()].into_iter().collect();
+    writeln!(f, "{} {} {}", a == b, a == c, set.len()).expect("Unable to write");
+
+    let tree = Expr::from(&parser::parse(parser::lex(
+        "(* (+ x 1) (- (+ x 1) (* (+ x 1) 2)))",
+    )));
+    let mut all = vec![];
+    subtrees(&tree, &mut all);
+    let mut seen = HashSet::new();
+    let repeated: Vec<String> = all
+        .into_iter()
+        .filter(|e| !seen.insert(*e))
+        .map(Expr::to_string)
+        .collect();
+    writeln!(f, "{:?}", repeated).expect("Unable to write");
+
+    // Built directly rather than parsed
+    let built = Expr::Pow(
+        Box::new(Expr::Lit(BigInt::from(2))),
+        Box::new(Expr::Float(Float(0.5))),
+    );
+    writeln!(f, "{} {:?}", built, built.to_exp().eval_f64()).expect("Unable to write");
+    // A negative literal prints as a subtraction, since -3 would lex as an operator and a 3
+    let negative = Expr::Mult(
+        Box::new(Expr::Pow(
+            Box::new(Expr::Lit(BigInt::from(-3))),
+            Box::new(Expr::Lit(BigInt::from(2))),
+        )),
+        Box::new(Expr::Float(Float(-1.5))),
+    );
+    let reparsed = parser::try_parse(parser::lex(&negative.to_string())).map(|ast| ast.eval_f64());
+    writeln!(f, "{} {:?}", negative, reparsed).expect("Unable to write");
+    writeln!(
+        f,
+        "{} {}",
+        Float(f64::NAN) == Float(f64::NAN),
+        Float(0.0) == Float(-0.0)
+    )
+    .expect("Unable to write");
+
+    // A builtin is only equal to itself
+    let max = Expr::from(&parser::parse(parser::lex("(max 1 2)")));
+    let again = Expr::from(&parser::parse(parser::lex("(max 1 2)")));
+    writeln!(f, "{} {}", max == max.clone(), max == again).expect("Unable to write");
+}
@@ -0,0 +1,26 @@
+use project::parser;
+use std::io::Write;
+
+fn main() {
+    // let input = std::fs::read_to_string("input").expect("Input file seems to be missing");
+    let input = "(- (^ 2 0.5 2) (* 1.5e-3 2e3) (^ 4 (- 1)))";
+
+    let tokens = parser::lex(&input);
+    let ast = parser::parse(tokens);
+
+    let evaled = ast.eval_f64().expect("evaluation failed");
+    let evaled_str = evaled.to_string();
+
+    let ast_str = ast.to_string();
+
+    let f = std::fs::OpenOptions::new()
+        .write(true)
+        .append(true)
+        .create(true)
+        .open("output")
+        .expect("unable to open output file");
+    let mut f = std::io::BufWriter::new(f);
+
+    write!(f, "{:?}\n", evaled_str).expect("Unable to write");
+    write!(f, "{:?}", ast_str).expect("Unable to write");
+}
@@ -0,0 +1,67 @@
+use project::number::{Number, NumberError};
+use project::parser::{self, Literal};
+use std::io::Write;
+
+// Whether a number is odd, which is all a user-defined domain needs to support
+#[derive(Clone, PartialEq, PartialOrd)]
+struct Odd(bool);
+
+impl Number for Odd {
+    fn from_literal(lit: Literal<'_>) -> Result<Odd, NumberError> {
+        match lit {
+            Literal::Int(n) => Ok(Odd(n.bit(0))),
+            Literal::Float(_) => Err(NumberError::NotAnInteger),
+        }
+    }
+
+    fn add(self, rhs: Odd) -> Result<Odd, NumberError> {
+        Ok(Odd(self.0 != rhs.0))
+    }
+
+    fn sub(self, rhs: Odd) -> Result<Odd, NumberError> {
+        Ok(Odd(self.0 != rhs.0))
+    }
+
+    fn mul(self, rhs: Odd) -> Result<Odd, NumberError> {
+        Ok(Odd(self.0 && rhs.0))
+    }
+
+    fn div(self, rhs: Odd) -> Result<Odd, NumberError> {
+        Ok(Odd(self.0 && rhs.0)) // Also wrong, odd / odd can be even
+    }
+
+    fn rem(self, rhs: Odd) -> Result<Odd, NumberError> {
+        Ok(Odd(self.0 && !rhs.0))
+    }
+
+    fn pow(self, _exponent: Odd) -> Result<Odd, NumberError> {
+        Ok(self) // Wrong for x ^ 0, which is fine for a test
+    }
+}
+
+fn main() {
+    // let input = std::fs::read_to_string("input").expect("Input file seems to be missing");
+    let input = "(* (^ 3 70) (- 7 2 1))";
+
+    let tokens = parser::lex(&input);
+    let ast = parser::parse(tokens);
+
+    let as_i32 = match ast.eval_as::<i32>() {
+        Ok(n) => n.to_string(),
+        Err(e) => e.to_string(),
+    };
+    let as_i128 = ast.eval_as::<i128>().expect("evaluation failed").to_string();
+    let as_odd = ast.eval_as::<Odd>().expect("evaluation failed").0.to_string();
+
+    let f = std::fs::OpenOptions::new()
+        .write(true)
+        .append(true)
+        .create(true)
+        .open("output")
+        .expect("unable to open output file");
+    let mut f = std::io::BufWriter::new(f);
+
+    write!(f, "{:?}\n", as_i32).expect("Unable to write");
+    write!(f, "{:?}\n", as_i128).expect("Unable to write");
+    write!(f, "{:?}", as_odd).expect("Unable to write");
+}
@@ -0,0 +1,118 @@
+use project::infix;
+use project::{BigInt, Expr};
+use project::parser;
+use std::io::Write;
+
+fn main() {
+    let inputs = [
+        "1 + 2 * 3 ^ 2",
+        "2 ^ 3 ^ 2",
+        "(2 ^ 3) ^ 2",
+        "10 - 4 - 3",
+        "10 - (4 - 3)",
+        "2 * (3 + 4)",
+        "-2 ^ 2",
+        "(-2) ^ 2",
+        "2 ^ -1",
+        "-x * y + -(a - b)",
+        "- -3",
+        "7 % 4 / 2",
+        "1.5 * 2",
+        "((((1))))",
+        "1 +",
+        "1 + * 2",
+        "(1 + 2",
+        "1 + 2)",
+        "1 2",
+        "if + 1",
+        "1 < 2",
+        "$",
+        "٣ + 1",
+        "1 + １",
+    ];
+
+    let f = std::fs::OpenOptions::new()
+        .write(true)
+        .append(true)
+        .create(true)
+        .open("output")
+        .expect("unable to open output file");
+    let mut f = std::io::BufWriter::new(f);
+
+    for input in inputs {
+        match infix::parse(parser::lex(input)) {
+            Ok(ast) => {
+                let value = match ast.eval_value() {
+                    Ok(v) => v.to_string(),
+                    Err(e) => e.to_string(),
+                };
+                let printed = infix::to_infix(&*ast).unwrap();
+                // Printing and parsing again gives back the same tree
+                let again = infix::parse(parser::lex(&printed)).unwrap();
+                writeln!(
+                    f,
+                    "{:?} {:?} {:?} {:?} {}",
+                    input,
+                    ast.to_string(),
+                    value,
+                    printed,
+                    again.to_string() == ast.to_string()
+                )
+                .expect("Unable to write");
+            }
+            Err(e) => writeln!(f, "{:?} {:?}", input, e.to_string()).expect("Unable to write"),
+        }
+    }
+
+    // Polish to infix, with only the parens the tree needs
+    let polish = [
+        "(+ 1 2 3 4)",
+        "(- 1 (- 2 3))",
+        "(+ 1 (+ 2 3))",
+        "(* (+ 1 2) (- 3 4))",
+        "(^ 2 3 4)",
+        "(^ (^ 2 3) 4)",
+        "(^ (- 2) 2)",
+        "(- (^ 2 2))",
+        "(- (- 2))",
+        "(* (- 2) 3)",
+        "(- 0 2)",
+        "(/ 1 2)",
+        "(+ x (* y (% z 2)))",
+        "(< 1 2)",
+    ];
+    for input in polish {
+        let ast = parser::try_parse(parser::lex(input)).unwrap();
+        match infix::to_infix(&*ast) {
+            Some(printed) => {
+                let again = infix::parse(parser::lex(&printed)).unwrap();
+                writeln!(f, "{:?} {:?} {}", input, printed, again.to_string() == ast.to_string())
+                    .expect("Unable to write");
+            }
+            None => writeln!(f, "{:?} no infix form", input).expect("Unable to write"),
+        }
+    }
+
+    // Parsing never makes a negative literal, but a tree built from an Expr can have one
+    let lit = |n: i64| Box::new(Expr::Lit(BigInt::from(n)));
+    let negative = [
+        Expr::Pow(lit(-3), lit(2)),
+        Expr::Pow(lit(2), lit(-3)),
+        Expr::Minus(lit(1), lit(-3)),
+        Expr::Mult(Box::new(Expr::Float(project::expr::Float(-1.5))), lit(2)),
+        Expr::Minus(Box::new(Expr::Lit(BigInt::from(0))), lit(-3)),
+    ];
+    for expr in negative {
+        let ast = expr.to_exp();
+        let printed = infix::to_infix(&*ast).unwrap();
+        let again = infix::parse(parser::lex(&printed)).unwrap();
+        writeln!(
+            f,
+            "{:?} {:?} {}",
+            ast.to_string(),
+            printed,
+            again.eval_f64() == ast.eval_f64()
+        )
+        .expect("Unable to write");
+    }
+}
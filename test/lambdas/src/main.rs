@@ -0,0 +1,73 @@
+use project::diagnostics::Diagnostic;
+use project::parser;
+use std::io::Write;
+
+fn main() {
+    let inputs = [
+        "((lambda (x y) (* x y)) 3 4)",
+        "(let ((double (lambda (x) (* x 2)))) (double 21))",
+        "(let ((n 10)) (let ((add_n (lambda (x) (+ x n)))) (let ((n 1000)) (add_n 5))))",
+        "(let ((compose (lambda (f g) (lambda (x) (f (g x)))))) ((compose (lambda (x) (* x x)) (lambda (x) (+ x 1))) 4))",
+        "((lambda () 7))",
+        "(lambda (x) (+ x 1))",
+        "(let ((f (lambda (x) x))) (= f f))",
+        "((lambda (x) x) 1 2)",
+        "(1 2)",
+        "(let ((x 1)) (x 2))",
+        "(lambda (x x) x)",
+        "(lambda x x)",
+    ];
+
+    let f = std::fs::OpenOptions::new()
+        .write(true)
+        .append(true)
+        .create(true)
+        .open("output")
+        .expect("unable to open output file");
+    let mut f = std::io::BufWriter::new(f);
+
+    for input in inputs {
+        let tokens = parser::lex(input);
+        let ast = match parser::try_parse(tokens) {
+            Ok(ast) => ast,
+            Err(e) => {
+                writeln!(f, "{:?}", e.to_string()).expect("Unable to write");
+                continue;
+            }
+        };
+        let evaled_str = match ast.eval_value() {
+            Ok(v) => v.to_string(),
+            Err(e) => e.to_string(),
+        };
+        writeln!(f, "{:?} {:?}", ast.to_string(), evaled_str).expect("Unable to write");
+    }
+
+    // Only eval_value evaluates functions, the evaluations over a Number reject them
+    let generic = [
+        "(let ((f (lambda (x) (* x 2)))) (f 21))",
+        "(abs (- 0 1.5))",
+        "(if (< 1 2) 3 ((lambda (x) x) 4))",
+    ];
+    for input in generic {
+        let ast = parser::parse(parser::lex(input));
+        writeln!(
+            f,
+            "{:?} {:?} {:?} {:?}",
+            input,
+            ast.eval_big().map_or_else(|e| e.to_string(), |n| n.to_string()),
+            ast.eval_rational().map_or_else(|e| e.to_string(), |n| n.to_string()),
+            ast.eval_f64().map_or_else(|e| e.to_string(), |n| n.to_string())
+        )
+        .expect("Unable to write");
+    }
+    let input = "(let ((f (lambda (x) (* x 2)))) (f 1.5))";
+    let ast = parser::parse(parser::lex(input));
+    let err = ast.eval_f64().unwrap_err();
+    write!(f, "{}", Diagnostic::from(&err).render(input)).expect("Unable to write");
+
+    // Arity errors point at both the call and the function
+    let input = "(let ((add (lambda (x y) (+ x y))))\n  (add 1))";
+    let ast = parser::parse(parser::lex(input));
+    let err = ast.eval_value().unwrap_err();
+    write!(f, "{}", Diagnostic::from(&err).render(input)).expect("Unable to write");
+}
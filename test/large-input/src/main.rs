@@ -0,0 +1,100 @@
+use project::diagnostics::Diagnostic;
+use project::parser;
+use std::io::Write;
+
+// A balanced tree of + forms with leaves copies of leaf, all on one line
+fn tree(leaves: usize, leaf: &str, out: &mut String) {
+    if leaves <= 1 {
+        out.push_str(leaf);
+    } else {
+        out.push_str("(+ ");
+        tree(leaves / 2, leaf, out);
+        out.push(' ');
+        tree(leaves - leaves / 2, leaf, out);
+        out.push(')');
+    }
+}
+
+fn main() {
+    let f = std::fs::OpenOptions::new()
+        .write(true)
+        .append(true)
+        .create(true)
+        .open("output")
+        .expect("unable to open output file");
+    let mut f = std::io::BufWriter::new(f);
+
+    // About a megabyte on a single line
+    let mut input = String::new();
+    tree(50_000, "(* 2 3 (- 1))", &mut input);
+    let toks = parser::lex(&input);
+    let last = *toks.last().unwrap();
+    writeln!(
+        f,
+        "{} {} {} {:?}",
+        input.len(),
+        toks.len(),
+        last.span.col == input.chars().count(),
+        last.span
+    )
+    .expect("Unable to write");
+    let ast = parser::try_parse(toks).unwrap();
+    writeln!(f, "{} {:?}", ast.eval(), ast.span()).expect("Unable to write");
+
+    // A flat form with a term for each operand
+    let mut input = String::from("(+");
+    for i in 0..2_000 {
+        input.push_str(&format!(" {}", i));
+    }
+    input.push(')');
+    let ast = parser::try_parse(parser::lex(&input)).unwrap();
+    writeln!(f, "{} {}", ast.eval(), ast.to_string().len()).expect("Unable to write");
+
+    // A million operands fold into a chain of a million operators, which evaluates and drops without recursing
+    let mut flat = String::from("(+");
+    for _ in 0..1_000_000 {
+        flat.push_str(" 1");
+    }
+    flat.push(')');
+    let ast = parser::try_parse(parser::lex(&flat)).unwrap();
+    writeln!(f, "{:?} {:?}", ast.try_eval(), ast.eval_big().map(|n| n.to_string()))
+        .expect("Unable to write");
+    drop(ast);
+
+    // Parsing does recurse once per level of nesting, so too deep an input is an error rather than a stack overflow
+    let mut nested = "(+ 1 ".repeat(20_000);
+    nested.push('1');
+    nested.push_str(&")".repeat(20_000));
+    let err = parser::try_parse(parser::lex(&nested)).err().unwrap();
+    writeln!(f, "{} {:?}", err, err.span()).expect("Unable to write");
+
+    // Errors at the end of a long line still point at the right column
+    input.insert(input.len() - 1, '@');
+    let err = parser::try_parse(parser::lex(&input)).err().unwrap();
+    writeln!(f, "{} {:?}", err, err.span()).expect("Unable to write");
+
+    // Columns count characters rather than bytes, and start again on each line
+    let inputs = vec![
+        "(+ 1 2 é)",
+        "(+ \"é\" é)",
+        "(+ 1\n   2\n   é)",
+        "(+ 1\r\n  (* 2\r\n     @))",
+        "(let ((ñ 1))\n\t(+ ñ é))",
+    ];
+    for input in inputs {
+        let toks = parser::lex(input);
+        let spans: Vec<String> = toks
+            .iter()
+            .map(|t| format!("{}:{}", t.span.line, t.span.col))
+            .collect();
+        writeln!(f, "{:?} {}", input, spans.join(" ")).expect("Unable to write");
+        if let Err(e) = parser::try_parse(toks) {
+            write!(f, "{}", Diagnostic::from(&e).render(input)).expect("Unable to write");
+        }
+    }
+
+    // expect still takes the first token off a Vec
+    let mut toks = parser::lex("(+ 1 2)");
+    let open = parser::expect(&mut toks, "(");
+    writeln!(f, "{:?} {}", open.span, parser::peek(&toks, 0)).expect("Unable to write");
+}
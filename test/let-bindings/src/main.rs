@@ -0,0 +1,95 @@
+use project::parser::EvalError;
+use project::{parser, scope};
+use std::io::Write;
+
+fn show<T: ToString>(result: Result<T, EvalError>) -> String {
+    match result {
+        Ok(n) => n.to_string(),
+        Err(e) => e.to_string(),
+    }
+}
+
+fn main() {
+    let inputs = [
+        "(let ((x 3) (y 4)) (* x y))",
+        "(let* ((x 3) (y (+ x 1))) (* x y))",
+        "(let ((x 3) (y (+ x 1))) (* x y))",
+        "(let ((x 1)) (let ((x (+ x 10)) (y x)) (+ x y)))",
+        "(let* ((x 1) (x (* x 5))) x)",
+        "(let () 7)",
+        "(let ((x 1) (x 2)) x)",
+        "(let (x 1) x)",
+        "(let ((x 1)) x x)",
+        "(let ((if 1)) 2)",
+    ];
+
+    let f = std::fs::OpenOptions::new()
+        .write(true)
+        .append(true)
+        .create(true)
+        .open("output")
+        .expect("unable to open output file");
+    let mut f = std::io::BufWriter::new(f);
+
+    for input in inputs {
+        let tokens = parser::lex(input);
+        let ast = match parser::try_parse(tokens) {
+            Ok(ast) => ast,
+            Err(e) => {
+                writeln!(f, "{:?}", e.to_string()).expect("Unable to write");
+                continue;
+            }
+        };
+        let evaled_str = match ast.eval_value() {
+            Ok(v) => v.to_string(),
+            Err(e) => e.to_string(),
+        };
+        // Printing and parsing again gives back the same expression
+        let printed = ast.to_string();
+        let reparsed = parser::parse(parser::lex(&printed)).to_string();
+        let shadowed: Vec<String> = scope::shadowing(&*ast)
+            .iter()
+            .map(|s| format!("{} at {}:{}", s.name, s.span.line, s.span.col))
+            .collect();
+        writeln!(
+            f,
+            "{:?} {:?} {:?} {:?}",
+            printed,
+            evaled_str,
+            printed == reparsed,
+            shadowed
+        )
+        .expect("Unable to write");
+    }
+
+    // Every numeric evaluation sees the names a let binds
+    let numeric = [
+        "(let ((x 3)) x)",
+        "(let ((x 3) (y 4)) (* x y))",
+        "(let* ((x 3) (y (+ x 1))) (* x y))",
+        "(let ((x 1)) (let ((x (+ x 10)) (y x)) (+ x y)))",
+        "(let ((x (^ 2 40))) (* x x))",
+        "(let ((x 1.5)) (* x 2))",
+        "(let ((x 7)) (/ x 2))",
+        "(let ((x 3)) (if (< x 5) (^ x 50) x))",
+        "(let ((x 2)) (let ((x 2.5)) (if (< x 3) 1 0)))",
+        "(let ((x 3) (y (+ x 1))) y)",
+        "(let ((x 1.5)) (if (< x 2) x 0))",
+        "(let ((x 1.5) (y 2)) ((lambda (z) (* z 3)) y))",
+    ];
+    for input in numeric {
+        let ast = parser::parse(parser::lex(input));
+        writeln!(
+            f,
+            "{:?} {:?} {:?} {:?} {:?} {:?} {:?}",
+            input,
+            show(ast.try_eval()),
+            show(ast.eval_as::<i32>()),
+            show(ast.eval_as::<i128>()),
+            show(ast.eval_big()),
+            show(ast.eval_rational()),
+            show(ast.eval_f64())
+        )
+        .expect("Unable to write");
+    }
+}
@@ -0,0 +1,100 @@
+use project::operators::{Assoc, Operator, OperatorTable, Unary};
+use project::parser::{self, MinusExp, ModExp, MultExp, ParserOptions};
+use std::io::Write;
+use std::rc::Rc;
+
+// The parsed tree, its span and its value, or why it failed
+fn describe(result: Result<Rc<dyn parser::Exp>, parser::ParseError>) -> String {
+    match result {
+        Ok(ast) => {
+            let value = match ast.eval_value() {
+                Ok(v) => v.to_string(),
+                Err(e) => e.to_string(),
+            };
+            format!("{} {}..{} {}", ast.to_string(), ast.span().start, ast.span().end, value)
+        }
+        Err(e) => e.to_string(),
+    }
+}
+
+fn main() {
+    let inputs = [
+        "(- 10 3 2 1)",
+        "(^ 2 3 2)",
+        // Right associative however many operands there are
+        "(^ 2 1 2 3)",
+        "(^ 4 3 2 1 0)",
+        "(+ 5)",
+        "(- 5)",
+        "(/ 5)",
+        "(* 5)",
+        "(% 5)",
+        "(^ 5)",
+        "- * 2 3 4",
+        "(* 2 3 4 5)",
+    ];
+
+    let f = std::fs::OpenOptions::new()
+        .write(true)
+        .append(true)
+        .create(true)
+        .open("output")
+        .expect("unable to open output file");
+    let mut f = std::io::BufWriter::new(f);
+
+    for input in inputs {
+        let result = describe(parser::try_parse(parser::lex(input)));
+        writeln!(f, "{:?} {:?}", input, result).expect("Unable to write");
+    }
+
+    // The spans of the nodes a right associative fold builds, from the outermost in
+    let ast = parser::try_parse(parser::lex("(^ 2 1 2 3)")).unwrap();
+    let mut node: &dyn parser::Exp = &*ast;
+    while let parser::Node::Binary(_, _, rhs) = node.node() {
+        writeln!(f, "{:?} {}..{}", node.to_string(), node.span().start, node.span().end).expect("Unable to write");
+        node = rhs;
+    }
+
+    // Registering operators
+    let mut options = ParserOptions::default();
+    options.operators.register(Operator::new(
+        "mod",
+        |lhs, rhs, span| Rc::new(ModExp { lhs, rhs, span }),
+        Assoc::Left,
+        2,
+        Unary::Identity,
+    ));
+    // Replacing one: * with a single operand is that operand
+    options.operators.register(Operator::new(
+        "*",
+        |lhs, rhs, span| Rc::new(MultExp { lhs, rhs, span }),
+        Assoc::Left,
+        1,
+        Unary::Identity,
+    ));
+    // A right associative subtraction
+    options.operators.register(Operator::new(
+        "rsub",
+        |lhs, rhs, span| Rc::new(MinusExp { lhs, rhs, span }),
+        Assoc::Right,
+        3,
+        Unary::Identity,
+    ));
+    for input in ["(mod 17 5)", "mod 17 5", "(mod 100 7 3)", "(mod 5)", "(* 4)", "(rsub 10 3 2)", "(rsub 10 3)", "(+ 1 (mod 9 4))"] {
+        let result = describe(parser::try_parse_with_options(parser::lex(input), &options));
+        writeln!(f, "{:?} {:?}", input, result).expect("Unable to write");
+    }
+
+    // Without any operators, + is just an invalid name
+    let none = ParserOptions {
+        operators: OperatorTable::new(),
+        ..ParserOptions::default()
+    };
+    for input in ["(+ 1 2)", "(< 1 2)"] {
+        let result = describe(parser::try_parse_with_options(parser::lex(input), &none));
+        writeln!(f, "{:?} {:?}", input, result).expect("Unable to write");
+    }
+
+    let symbols: Vec<String> = OperatorTable::default().iter().map(|op| op.symbol.clone()).collect();
+    writeln!(f, "{:?}", symbols).expect("Unable to write");
+}
@@ -0,0 +1,40 @@
+use project::parser;
+use std::io::Write;
+
+fn main() {
+    let inputs = [
+        "(+ 1 2",
+        "()",
+        "(+)",
+        "((^ 10) 1)",
+        "(1 2)",
+        "(+ 1 (^ 1))",
+        "$x",
+        "(+ if 1)",
+        "+ 1 2 3",
+        "99999999999",
+        "(* 2x 3)",
+        "(+ 2e 1)",
+        "(- 1.5.2 1)",
+        "٣",
+        "(+ １ 2)",
+        "(* 1٣ 2)",
+    ];
+
+    let f = std::fs::OpenOptions::new()
+        .write(true)
+        .append(true)
+        .create(true)
+        .open("output")
+        .expect("unable to open output file");
+    let mut f = std::io::BufWriter::new(f);
+
+    for input in inputs {
+        let tokens = parser::lex(input);
+        let err = match parser::try_parse(tokens) {
+            Ok(ast) => ast.to_string(),
+            Err(e) => e.to_string(),
+        };
+        writeln!(f, "{:?}", err).expect("Unable to write");
+    }
+}
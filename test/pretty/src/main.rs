@@ -0,0 +1,98 @@
+use project::parser;
+use project::pretty::Printer;
+use std::io::Write;
+
+fn main() {
+    let mut fold = String::from("(- 0");
+    for i in 1..40 {
+        fold.push_str(&format!(" {}", i));
+    }
+    fold.push(')');
+
+    let inputs = [
+        ("(+ 1 2)", 80),
+        ("(let ((x (+ 1 2)) (y (* 3 4))) (+ (* x y) (- x y)))", 24),
+        (
+            "(let* ((x 1) (y (+ x 1)) (z (* y 2))) (if (< x z) (max x y z) (min x y z)))",
+            30,
+        ),
+        (
+            "((lambda (a b) (if (< a b) (max a b 1000000) (min a b 1000000))) 3 4)",
+            30,
+        ),
+        ("(and (or (< 1 2) (= 3 4)) (not (>= 5 6)))", 20),
+        ("(^ 2 3 4 5)", 10),
+        ("(+ 1.5 2e10 123456789012345678901234567890)", 20),
+        ("(+ 1 (* 2 3))", 1),
+        (fold.as_str(), 80),
+    ];
+
+    let f = std::fs::OpenOptions::new()
+        .write(true)
+        .append(true)
+        .create(true)
+        .open("output")
+        .expect("unable to open output file");
+    let mut f = std::io::BufWriter::new(f);
+
+    for (input, width) in inputs {
+        let ast = parser::try_parse(parser::lex(input)).unwrap();
+        for printer in [Printer::new(width), Printer::compact(width)] {
+            let printed = printer.print(&*ast);
+            // Breaking lines changes nothing but whitespace, so the output parses to the same tree
+            let again = parser::try_parse(parser::lex(&printed)).unwrap();
+            writeln!(
+                f,
+                "width {} compact {} same tree {}",
+                width,
+                printer.compact,
+                again.to_string() == ast.to_string()
+            )
+            .expect("Unable to write");
+            writeln!(f, "{}", printed).expect("Unable to write");
+        }
+        // Wide enough for everything, it prints what to_string does
+        writeln!(
+            f,
+            "{}",
+            Printer::new(usize::MAX).print(&*ast) == ast.to_string()
+        )
+        .expect("Unable to write");
+    }
+
+    // The shape of public-5, a left fold where every level is too wide for one line
+    // Each level indents by 2, so 30 terms fit in 80 columns, while 1000 can't and only step in by 2 a level
+    for terms in [30, 1000] {
+        let mut fold = String::from("(- 0");
+        for i in 1..terms {
+            fold.push_str(&format!(" {}", i));
+        }
+        fold.push(')');
+        let ast = parser::try_parse(parser::lex(&fold)).unwrap();
+        for printer in [Printer::new(80), Printer::compact(80)] {
+            let printed = printer.print(&*ast);
+            let widest = printed.lines().map(|line| line.chars().count()).max().unwrap();
+            writeln!(
+                f,
+                "terms {} compact {} widest {} fits {}",
+                terms,
+                printer.compact,
+                widest,
+                widest <= 80
+            )
+            .expect("Unable to write");
+        }
+    }
+
+    let ast = parser::parse(parser::lex("(+ 1 (- 2 (* 3 @)))"));
+    writeln!(f, "{}", Printer::new(5).print(&*ast)).expect("Unable to write");
+
+    let program = parser::parse_program(parser::lex(
+        "(define (fact n) (if (= n 0) 1 (* n (fact (- n 1))))) (define n 10) (fact n)",
+    ))
+    .unwrap();
+    writeln!(f, "{}", Printer::new(30).print_program(&program)).expect("Unable to write");
+    let mut printer = Printer::new(30);
+    printer.indent = 4;
+    writeln!(f, "{}", printer.print_program(&program)).expect("Unable to write");
+}
@@ -0,0 +1,63 @@
+use project::parser;
+use project::Env;
+use std::io::Write;
+
+fn main() {
+    let inputs = [
+        "(define (square x) (* x x))
+         (define n 12)
+         (square n)",
+        "(define (fact n) (if (= n 0) 1 (* n (fact (- n 1)))))
+         (fact 10)
+         (fact 20)",
+        "(define (even n) (if (= n 0) (< 0 1) (odd (- n 1))))
+         (define (odd n) (if (= n 0) (< 1 0) (even (- n 1))))
+         (even 10) (odd 7) (even 7)",
+        "(define x 1)
+         (define (get_x) x)
+         (define x 2)
+         (get_x)",
+        "(define (add_rate amount) (+ amount rate))
+         (add_rate 100)",
+        "(define (f x) x)",
+        "(+ 1 (define x 2))",
+        "(define 1 2)",
+        "(define x 1 2)",
+    ];
+
+    let mut env = Env::new();
+    env.set("rate", 5);
+
+    let f = std::fs::OpenOptions::new()
+        .write(true)
+        .append(true)
+        .create(true)
+        .open("output")
+        .expect("unable to open output file");
+    let mut f = std::io::BufWriter::new(f);
+
+    for input in inputs {
+        let tokens = parser::lex(input);
+        let program = match parser::parse_program(tokens) {
+            Ok(program) => program,
+            Err(e) => {
+                writeln!(f, "{:?}", e.to_string()).expect("Unable to write");
+                continue;
+            }
+        };
+        let all = match program.eval_all_with(&env) {
+            Ok(values) => values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" "),
+            Err(e) => e.to_string(),
+        };
+        let last = match program.eval_with(&env) {
+            Ok(Some(v)) => v.to_string(),
+            Ok(None) => "nothing".to_string(),
+            Err(e) => e.to_string(),
+        };
+        writeln!(f, "{:?} {:?} {:?}", program.to_string(), all, last).expect("Unable to write");
+    }
+
+    // A single expression can't define anything
+    let err = parser::try_parse(parser::lex("(define x 1)")).err().unwrap();
+    writeln!(f, "{:?}", err.to_string()).expect("Unable to write");
+}
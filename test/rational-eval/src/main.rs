@@ -0,0 +1,24 @@
+use project::parser;
+use std::io::Write;
+
+fn main() {
+    let inputs = ["(- 1 (^ 2 (- 3)))", "(* (^ 3 (- 2)) (^ 3 2))", "(^ (^ 2 (- 1)) (- 2) 3)", "(^ 0 (- 1))", "(^ 2 (^ 2 (- 1)))"];
+
+    let f = std::fs::OpenOptions::new()
+        .write(true)
+        .append(true)
+        .create(true)
+        .open("output")
+        .expect("unable to open output file");
+    let mut f = std::io::BufWriter::new(f);
+
+    for input in inputs {
+        let tokens = parser::lex(input);
+        let ast = parser::parse(tokens);
+        let evaled_str = match ast.eval_rational() {
+            Ok(r) => r.to_string(),
+            Err(e) => e.to_string(),
+        };
+        writeln!(f, "{:?}", evaled_str).expect("Unable to write");
+    }
+}
@@ -0,0 +1,100 @@
+use project::pretty::Printer;
+use project::{infix, parser};
+use std::io::Write;
+
+fn main() {
+    let inputs = [
+        "(+ 1 2 3 4)",
+        "(- 2)",
+        "(/ 1 2)",
+        "(+ 3)",
+        "(^ 2 3 4)",
+        "(^ (^ 2 3) 4)",
+        "(- 0 2)",
+        "(- 0 2 3)",
+        "(- (- 2) 3 4)",
+        "(- (- (- 2)))",
+        "(* (+ 1 2 3) (- 4 5) 6)",
+        "(- (- 1 2) (- 3 4))",
+        "(% 1 2 3)",
+        "(/ 1 2 3)",
+        "(and (< 1 2) (< 2 3) (< 3 4))",
+        "(let ((x (+ 1 2 3))) (* x x x))",
+        "((lambda (a) (^ a a a)) (- 2 1 1))",
+        "(max (+ 1 2 3) 4 5)",
+        "(+ 1 2 @)",
+    ];
+
+    let f = std::fs::OpenOptions::new()
+        .write(true)
+        .append(true)
+        .create(true)
+        .open("output")
+        .expect("unable to open output file");
+    let mut f = std::io::BufWriter::new(f);
+
+    for input in inputs {
+        let ast = parser::parse(parser::lex(input));
+        let sugared = Printer::sugared(&*ast);
+        let again = parser::parse(parser::lex(&sugared));
+        writeln!(
+            f,
+            "{:?} {:?} {:?} {}",
+            input,
+            ast.to_string(),
+            sugared,
+            again.to_string() == ast.to_string()
+        )
+        .expect("Unable to write");
+    }
+
+    // Unary minus in infix is the same desugared form
+    for input in ["-x - -y", "-(2 ^ 3) * 4", "1 - 2 - 3 - 4", "2 ^ 3 ^ 4"] {
+        let ast = infix::parse(parser::lex(input)).unwrap();
+        writeln!(
+            f,
+            "{:?} {:?} {:?}",
+            input,
+            ast.to_string(),
+            Printer::sugared(&*ast)
+        )
+        .expect("Unable to write");
+    }
+
+    // The 1000 operands of public-5 come back as they were written
+    let mut fold = String::from("(- 0");
+    for i in 1..1000 {
+        fold.push_str(&format!(" {}", i));
+    }
+    fold.push(')');
+    let ast = parser::parse(parser::lex(&fold));
+    writeln!(f, "{}", Printer::sugared(&*ast) == fold).expect("Unable to write");
+    let printer = Printer {
+        resugar: true,
+        ..Printer::compact(80)
+    };
+    let printed = printer.print(&*ast);
+    let again = parser::parse(parser::lex(&printed));
+    writeln!(
+        f,
+        "{} {}",
+        printed.lines().count(),
+        again.to_string() == ast.to_string()
+    )
+    .expect("Unable to write");
+    writeln!(
+        f,
+        "{}",
+        printed.lines().take(3).collect::<Vec<_>>().join("\n")
+    )
+    .expect("Unable to write");
+
+    let ast = parser::parse(parser::lex(
+        "(let* ((x (+ 1 2 3 4 5)) (y (* x x x))) (if (< x y) (- y x 1) (- x)))",
+    ));
+    let printer = Printer {
+        resugar: true,
+        ..Printer::new(24)
+    };
+    writeln!(f, "{}", printer.print(&*ast)).expect("Unable to write");
+}
@@ -0,0 +1,105 @@
+use project::parser;
+use project::rpn;
+use project::{BigInt, Expr};
+use std::io::Write;
+
+fn main() {
+    let inputs = [
+        "1 2 + 3 *",
+        "2 3 2 ^ ^",
+        "2 3 ^ 2 ^",
+        "10 4 - 3 -",
+        "10 4 3 - -",
+        "x y * 2 %",
+        "1.5 2 *",
+        "7",
+        "",
+        "1 +",
+        "1 2",
+        "1 2 3 +",
+        "1 2 <",
+        "( 1 2 + )",
+    ];
+
+    let f = std::fs::OpenOptions::new()
+        .write(true)
+        .append(true)
+        .create(true)
+        .open("output")
+        .expect("unable to open output file");
+    let mut f = std::io::BufWriter::new(f);
+
+    for input in inputs {
+        match rpn::parse(parser::lex(input)) {
+            Ok(ast) => {
+                let value = match ast.eval_value() {
+                    Ok(v) => v.to_string(),
+                    Err(e) => e.to_string(),
+                };
+                writeln!(
+                    f,
+                    "{:?} {:?} {:?} {:?}",
+                    input,
+                    ast.to_string(),
+                    value,
+                    rpn::to_rpn(&*ast)
+                )
+                .expect("Unable to write");
+            }
+            Err(e) => writeln!(f, "{:?} {:?}", input, e.to_string()).expect("Unable to write"),
+        }
+    }
+
+    // Polish to RPN and back gives the same tree
+    let polish = [
+        "(+ 1 2 3 4)",
+        "(^ 2 3 4)",
+        "(^ (^ 2 3) 4)",
+        "(^ 4 3 2 1 0)",
+        "(- 2)",
+        "(/ 1 2)",
+        "(* (+ 1 2) (- 3 4) (/ 5 6))",
+        "(% (^ x 2) 1e20)",
+        "(+ 1 (< 1 2))",
+    ];
+    for input in polish {
+        let ast = parser::try_parse(parser::lex(input)).unwrap();
+        match rpn::to_rpn(&*ast) {
+            Some(printed) => {
+                let again = rpn::parse(parser::lex(&printed)).unwrap();
+                writeln!(
+                    f,
+                    "{:?} {:?} {:?} {}",
+                    input,
+                    printed,
+                    again.to_string(),
+                    again.to_string() == ast.to_string()
+                )
+                .expect("Unable to write");
+            }
+            None => writeln!(f, "{:?} no RPN form", input).expect("Unable to write"),
+        }
+    }
+
+    // Parsing never makes a negative literal, but a tree built from an Expr can have one
+    let lit = |n: i64| Box::new(Expr::Lit(BigInt::from(n)));
+    let negative = [
+        Expr::Pow(lit(-3), lit(2)),
+        Expr::Minus(lit(1), lit(-3)),
+        Expr::Mult(Box::new(Expr::Float(project::expr::Float(-1.5))), lit(2)),
+    ];
+    for expr in negative {
+        let ast = expr.to_exp();
+        let printed = rpn::to_rpn(&*ast).unwrap();
+        let again = rpn::parse(parser::lex(&printed)).unwrap();
+        writeln!(
+            f,
+            "{:?} {:?} {:?} {}",
+            ast.to_string(),
+            printed,
+            again.to_string(),
+            again.eval_f64() == ast.eval_f64()
+        )
+        .expect("Unable to write");
+    }
+}
@@ -0,0 +1,53 @@
+use project::parser::{self, Exp, Node, Span};
+use std::io::Write;
+
+fn span(span: Span) -> String {
+    format!("{}:{} {}..{}", span.line, span.col, span.start, span.end)
+}
+
+// Every node of the tree with its span, outermost first
+fn nodes(exp: &dyn Exp, out: &mut Vec<String>) {
+    out.push(format!("{} @ {}", exp.to_string(), span(exp.span())));
+    if let Node::Binary(_, lhs, rhs) = exp.node() {
+        nodes(lhs, out);
+        nodes(rhs, out);
+    }
+}
+
+fn main() {
+    let inputs = [
+        "(+ 1 22)",
+        "- 10 (* 2 3)",
+        "(* 2\n   (- 3 4))",
+        "(+ 1\r\n  2)",
+        "(+ 1 2 3)",
+        "(- 5)",
+        "(+ 1\r\n  (^ 2))",
+    ];
+
+    let f = std::fs::OpenOptions::new()
+        .write(true)
+        .append(true)
+        .create(true)
+        .open("output")
+        .expect("unable to open output file");
+    let mut f = std::io::BufWriter::new(f);
+
+    for input in inputs {
+        let tokens = parser::lex(input);
+        let toks: Vec<String> = tokens
+            .iter()
+            .map(|tok| format!("{} @ {}", tok.text, span(tok.span)))
+            .collect();
+        writeln!(f, "{:?}", input).expect("Unable to write");
+        writeln!(f, "tokens: {}", toks.join(", ")).expect("Unable to write");
+        match parser::try_parse(tokens) {
+            Ok(ast) => {
+                let mut out = vec![];
+                nodes(&*ast, &mut out);
+                writeln!(f, "nodes: {}", out.join(", ")).expect("Unable to write");
+            }
+            Err(e) => writeln!(f, "error: {} @ {}", e, span(e.span())).expect("Unable to write"),
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use project::parser;
+use std::io::Write;
+
+fn main() {
+    // The first four make a million calls in tail position, which would overflow the stack if every call took a frame
+    let inputs = [
+        "(define (sum n acc) (if (= n 0) acc (sum (- n 1) (+ acc n))))
+         (sum 1000000 0)",
+        "(define (even n) (if (= n 0) (= 0 0) (odd (- n 1))))
+         (define (odd n) (if (= n 0) (= 0 1) (even (- n 1))))
+         (even 1000000) (odd 7)",
+        "(define (count n) (let ((m (- n 1))) (if (< m 0) n (count m))))
+         (count 1000000)",
+        "(define (loop n) (if (= n 0) 0 ((lambda (k) (loop k)) (- n 1))))
+         (loop 1000000)",
+        // Arguments are evaluated before the call replaces the frame
+        "(define (fib n a b) (if (= n 0) a (fib (- n 1) b (+ a b))))
+         (fib 90 0 1)",
+        // A call that isn't in tail position still takes a frame
+        "(define (fact n) (if (= n 0) 1 (* n (fact (- n 1)))))
+         (fact 20)",
+        // Errors from the end of a loop are still reported
+        "(define (spin n) (if (= n 0) (+ (= 0 0) 1) (spin (- n 1))))
+         (spin 10)",
+    ];
+
+    let f = std::fs::OpenOptions::new()
+        .write(true)
+        .append(true)
+        .create(true)
+        .open("output")
+        .expect("unable to open output file");
+    let mut f = std::io::BufWriter::new(f);
+
+    for input in inputs {
+        let program = parser::parse_program(parser::lex(input)).unwrap();
+        let all = match program.eval_all() {
+            Ok(values) => values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" "),
+            Err(e) => e.to_string(),
+        };
+        writeln!(f, "{:?}", all).expect("Unable to write");
+    }
+}
@@ -0,0 +1,42 @@
+use project::parser;
+use project::Env;
+use std::io::Write;
+
+fn main() {
+    let inputs = [
+        "(* x (+ y 1))",
+        "(if (< rate 10) (* rate 2) rate)",
+        "(and is_open (> count 0))",
+        "(+ x missing)",
+    ];
+
+    let mut env = Env::new();
+    env.set("x", 2);
+    env.set("y", 3);
+    env.set("rate", 7);
+    env.set("is_open", true);
+    env.set("count", 0);
+
+    let f = std::fs::OpenOptions::new()
+        .write(true)
+        .append(true)
+        .create(true)
+        .open("output")
+        .expect("unable to open output file");
+    let mut f = std::io::BufWriter::new(f);
+
+    for input in inputs {
+        let tokens = parser::lex(input);
+        let ast = parser::parse(tokens);
+        let evaled_str = match ast.eval_with(&env) {
+            Ok(v) => v.to_string(),
+            Err(e) => e.to_string(),
+        };
+        let without_env = match ast.try_eval() {
+            Ok(n) => n.to_string(),
+            Err(e) => e.to_string(),
+        };
+        writeln!(f, "{:?} {:?} {:?}", ast.to_string(), evaled_str, without_env)
+            .expect("Unable to write");
+    }
+}